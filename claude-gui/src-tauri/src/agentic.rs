@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use tauri::command;
 
@@ -83,9 +84,138 @@ fn is_safe_command(cmd: &str) -> bool {
     false
 }
 
+/// `cmd.exe` built-ins from [`SAFE_COMMANDS`]; they have no executable of
+/// their own, so on Windows they still go through the shell
+const SHELL_BUILTINS: &[&str] = &["dir", "ver", "set", "echo", "path", "type", "date", "time"];
+
+/// Characters `cmd`/`sh` would interpret; rejected when a shell is unavoidable
+const SHELL_METACHARACTERS: &[char] = &['&', '|', ';', '<', '>', '`', '$', '^', '%', '(', ')', '\n', '\r'];
+
+/// Split a command line into program and arguments. Double and single quotes
+/// group words; nothing else is interpreted.
+fn split_command_line(command: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+
+    for c in command.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    args.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+
+    if quote.is_some() {
+        return Err("Unterminated quote in command".to_string());
+    }
+    if in_word {
+        args.push(current);
+    }
+    if args.is_empty() {
+        return Err("Empty command".to_string());
+    }
+    Ok(args)
+}
+
+fn needs_shell(program: &str) -> bool {
+    cfg!(target_os = "windows") && SHELL_BUILTINS.contains(&program.to_lowercase().as_str())
+}
+
+fn shell_command(command: &str) -> Command {
+    let (shell, flag) = if cfg!(target_os = "windows") { ("cmd", "/C") } else { ("sh", "-c") };
+    let mut cmd = Command::new(shell);
+    cmd.args([flag, command]);
+    cmd
+}
+
+/// Build the process for an allowlisted command. Programs run directly with
+/// parsed arguments, so there is no shell to inject into; only `cmd`
+/// built-ins use the shell, and then only without metacharacters.
+fn safe_command(command: &str) -> Result<Command, String> {
+    let args = split_command_line(command)?;
+
+    if needs_shell(&args[0]) {
+        if command.contains(SHELL_METACHARACTERS) {
+            return Err(format!("Shell metacharacters are not allowed in safe mode: {}", command));
+        }
+        return Ok(shell_command(command));
+    }
+
+    let mut cmd = Command::new(&args[0]);
+    cmd.args(&args[1..]);
+    Ok(cmd)
+}
+
+/// Directory commands may run in; the user's home directory
+fn command_root() -> Result<PathBuf, String> {
+    dirs::home_dir().ok_or_else(|| "Could not determine home directory".to_string())
+}
+
+/// Resolve `cwd` (symlinks and `..` included) and require it to be a
+/// directory inside `root`
+fn resolve_cwd(cwd: &str, root: &Path) -> Result<PathBuf, String> {
+    let root = root
+        .canonicalize()
+        .map_err(|e| format!("Invalid command root: {}", e))?;
+    let dir = Path::new(cwd)
+        .canonicalize()
+        .map_err(|e| format!("Invalid working directory '{}': {}", cwd, e))?;
+
+    if !dir.starts_with(&root) {
+        return Err(format!("Working directory must be inside {}", root.display()));
+    }
+    if !dir.is_dir() {
+        return Err(format!("Not a directory: {}", dir.display()));
+    }
+    Ok(strip_verbatim(dir))
+}
+
+/// `canonicalize` returns `\\?\C:\...` paths on Windows, which `cmd.exe`
+/// won't take as a working directory; turn them back into plain paths
+fn strip_verbatim(path: PathBuf) -> PathBuf {
+    if !cfg!(windows) {
+        return path;
+    }
+    let Some(text) = path.to_str() else {
+        return path;
+    };
+    if let Some(share) = text.strip_prefix(r"\\?\UNC\") {
+        PathBuf::from(format!(r"\\{}", share))
+    } else if let Some(plain) = text.strip_prefix(r"\\?\") {
+        PathBuf::from(plain)
+    } else {
+        path
+    }
+}
+
 /// Execute a system command (safe mode)
+///
+/// `cwd` must resolve to a directory inside the user's home directory;
+/// without it the command runs in the app's working directory.
+///
+/// In safe mode the command must be allowlisted and runs without a shell
+/// where possible (see [`safe_command`]); otherwise it is passed to the shell.
 #[command]
-pub async fn execute_command(command: String, safe_mode: bool) -> Result<CommandResult, String> {
+pub async fn execute_command(
+    command: String,
+    safe_mode: bool,
+    cwd: Option<String>,
+) -> Result<CommandResult, String> {
     // In safe mode, validate command
     if safe_mode && !is_safe_command(&command) {
         return Err(format!(
@@ -96,15 +226,17 @@ pub async fn execute_command(command: String, safe_mode: bool) -> Result<Command
 
     tracing::info!("Executing command: {}", command);
 
-    #[cfg(target_os = "windows")]
-    let output = Command::new("cmd")
-        .args(["/C", &command])
-        .output()
-        .map_err(|e| format!("Failed to execute command: {}", e))?;
+    let mut process = if safe_mode {
+        safe_command(&command)?
+    } else {
+        shell_command(&command)
+    };
+
+    if let Some(cwd) = cwd {
+        process.current_dir(resolve_cwd(&cwd, &command_root()?)?);
+    }
 
-    #[cfg(not(target_os = "windows"))]
-    let output = Command::new("sh")
-        .args(["-c", &command])
+    let output = process
         .output()
         .map_err(|e| format!("Failed to execute command: {}", e))?;
 
@@ -116,3 +248,65 @@ pub async fn execute_command(command: String, safe_mode: bool) -> Result<Command
     })
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_command_line() {
+        assert_eq!(split_command_line("ping -n 1 localhost").unwrap(), ["ping", "-n", "1", "localhost"]);
+        assert_eq!(
+            split_command_line(r#"findstr "hello world" 'notes.txt'"#).unwrap(),
+            ["findstr", "hello world", "notes.txt"]
+        );
+        assert_eq!(split_command_line(r#"echo """#).unwrap(), ["echo", ""]);
+        // Metacharacters are plain argument text without a shell
+        assert_eq!(split_command_line("echo a;b").unwrap(), ["echo", "a;b"]);
+        assert!(split_command_line("echo \"unterminated").is_err());
+        assert!(split_command_line("   ").is_err());
+    }
+
+    #[test]
+    fn test_resolve_cwd_stays_inside_root() {
+        let root = std::env::temp_dir().join(format!("cwd-test-{}", uuid::Uuid::new_v4()));
+        let project = root.join("project");
+        std::fs::create_dir_all(&project).unwrap();
+
+        assert_eq!(
+            resolve_cwd(project.to_str().unwrap(), &root).unwrap(),
+            strip_verbatim(project.canonicalize().unwrap())
+        );
+
+        let escape = project.join("..").join("..");
+        assert!(resolve_cwd(escape.to_str().unwrap(), &root).is_err());
+        assert!(resolve_cwd(root.join("missing").to_str().unwrap(), &root).is_err());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_resolve_cwd_is_not_verbatim() {
+        let root = std::env::temp_dir().join(format!("cwd-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+
+        let dir = resolve_cwd(root.to_str().unwrap(), &root).unwrap();
+        assert!(!dir.to_string_lossy().starts_with(r"\\?\"));
+        assert!(dir.is_dir());
+
+        assert_eq!(strip_verbatim(PathBuf::from(r"\\?\C:\Users\me")), PathBuf::from(r"C:\Users\me"));
+        assert_eq!(
+            strip_verbatim(PathBuf::from(r"\\?\UNC\server\share")),
+            PathBuf::from(r"\\server\share")
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_only_builtins_need_shell() {
+        assert!(!needs_shell("ping"));
+        assert!(!needs_shell("hostname"));
+        assert_eq!(needs_shell("DIR"), cfg!(target_os = "windows"));
+    }
+}
@@ -0,0 +1,310 @@
+//! Local HTTP transport for the bridge
+//!
+//! Agents that send many requests can talk to the app over HTTP instead of
+//! polling `bridge.json`. The server binds 127.0.0.1 only and exposes:
+//!
+//! - `GET  /bridge/state`        current `BridgeData`
+//! - `POST /bridge/approve/{id}` approve a request
+//! - `POST /bridge/reject/{id}`  reject a request
+//! - `GET  /bridge/events`       SSE stream of `BridgeData`, sent on every change
+//!
+//! The file stays the source of truth, so the HTTP and file transports can be
+//! mixed freely.
+//!
+//! Loopback alone does not keep web pages out: a page can POST to
+//! 127.0.0.1 without CORS, and DNS rebinding lets it read responses. So
+//! every request must carry `Authorization: Bearer <token>` with the token
+//! returned by `start_bridge_server`, name `127.0.0.1:<port>` or
+//! `localhost:<port>` as its `Host`, and carry no `Origin` header.
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::bridge::{self, BridgeData};
+
+const DEFAULT_PORT: u16 = 7732;
+/// How often SSE streams check the bridge file for changes
+const EVENT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// Comment line sent on idle streams so dead clients are noticed
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Where to reach the bridge server and the token it requires
+#[derive(Debug, Clone, Serialize)]
+pub struct BridgeServerInfo {
+    pub port: u16,
+    pub token: String,
+}
+
+struct RunningServer {
+    server: Arc<Server>,
+    info: BridgeServerInfo,
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+lazy_static::lazy_static! {
+    static ref BRIDGE_SERVER: Mutex<Option<RunningServer>> = Mutex::new(None);
+}
+
+#[derive(Debug, PartialEq)]
+enum Route<'a> {
+    State,
+    Approve(&'a str),
+    Reject(&'a str),
+    Events,
+    NotFound,
+}
+
+fn route<'a>(method: &Method, url: &'a str) -> Route<'a> {
+    let path = url.split('?').next().unwrap_or_default().trim_end_matches('/');
+
+    match (method, path) {
+        (Method::Get, "/bridge/state") => Route::State,
+        (Method::Get, "/bridge/events") => Route::Events,
+        (Method::Post, _) => {
+            if let Some(id) = path.strip_prefix("/bridge/approve/").filter(|id| !id.is_empty()) {
+                Route::Approve(id)
+            } else if let Some(id) = path.strip_prefix("/bridge/reject/").filter(|id| !id.is_empty()) {
+                Route::Reject(id)
+            } else {
+                Route::NotFound
+            }
+        }
+        _ => Route::NotFound,
+    }
+}
+
+/// Why a request was refused, as an HTTP status
+fn check_access(headers: &[Header], port: u16, token: &str) -> Result<(), u16> {
+    let header = |name: &'static str| {
+        headers
+            .iter()
+            .find(|h| h.field.equiv(name))
+            .map(|h| h.value.as_str().trim())
+    };
+
+    // Browsers always send Origin on cross-site requests; agents have no reason to
+    if header("Origin").is_some() {
+        return Err(403);
+    }
+
+    let host_ok = header("Host")
+        .is_some_and(|host| host == format!("127.0.0.1:{}", port) || host == format!("localhost:{}", port));
+    if !host_ok {
+        return Err(403);
+    }
+
+    match header("Authorization").and_then(|value| value.strip_prefix("Bearer ")) {
+        Some(given) if given.trim() == token => Ok(()),
+        _ => Err(401),
+    }
+}
+
+fn json_response(body: Result<BridgeData, String>) -> Response<std::io::Cursor<Vec<u8>>> {
+    let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap();
+
+    let (status, body) = match body.and_then(|data| serde_json::to_string(&data).map_err(|e| e.to_string())) {
+        Ok(json) => (200, json),
+        Err(e) => (500, serde_json::json!({ "error": e }).to_string()),
+    };
+
+    Response::from_string(body).with_status_code(status).with_header(content_type)
+}
+
+/// Stream `BridgeData` as SSE until the client disconnects or the server stops
+fn stream_events(request: Request, stop: Arc<AtomicBool>) {
+    let mut writer = request.into_writer();
+    let headers = "HTTP/1.1 200 OK\r\n\
+                   Content-Type: text/event-stream\r\n\
+                   Cache-Control: no-cache\r\n\
+                   Connection: keep-alive\r\n\r\n";
+    if writer.write_all(headers.as_bytes()).and_then(|_| writer.flush()).is_err() {
+        return;
+    }
+
+    let mut last_sent = String::new();
+    let mut last_write = Instant::now();
+
+    while !stop.load(Ordering::Relaxed) {
+        let json = bridge::get_bridge_state()
+            .ok()
+            .and_then(|data| serde_json::to_string(&data).ok())
+            .unwrap_or_default();
+
+        let message = if !json.is_empty() && json != last_sent {
+            let message = format!("data: {}\n\n", json);
+            last_sent = json;
+            Some(message)
+        } else if last_write.elapsed() >= KEEPALIVE_INTERVAL {
+            Some(": keepalive\n\n".to_string())
+        } else {
+            None
+        };
+
+        if let Some(message) = message {
+            if writer.write_all(message.as_bytes()).and_then(|_| writer.flush()).is_err() {
+                return;
+            }
+            last_write = Instant::now();
+        }
+
+        std::thread::sleep(EVENT_POLL_INTERVAL);
+    }
+}
+
+fn serve(server: Arc<Server>, info: BridgeServerInfo, stop: Arc<AtomicBool>) {
+    for request in server.incoming_requests() {
+        if stop.load(Ordering::Relaxed) {
+            break;
+        }
+
+        if let Err(status) = check_access(request.headers(), info.port, &info.token) {
+            let message = if status == 401 { "Unauthorized" } else { "Forbidden" };
+            if let Err(e) = request.respond(Response::from_string(message).with_status_code(status)) {
+                tracing::warn!("Bridge server failed to respond: {}", e);
+            }
+            continue;
+        }
+
+        let response = match route(request.method(), request.url()) {
+            Route::State => json_response(bridge::get_bridge_state()),
+            Route::Approve(id) => json_response(bridge::approve_bridge_request(id.to_string())),
+            Route::Reject(id) => json_response(bridge::reject_bridge_request(id.to_string())),
+            Route::Events => {
+                // Each stream holds its connection open, so give it a thread
+                let stop = stop.clone();
+                std::thread::spawn(move || stream_events(request, stop));
+                continue;
+            }
+            Route::NotFound => Response::from_string("Not found").with_status_code(404),
+        };
+
+        if let Err(e) = request.respond(response) {
+            tracing::warn!("Bridge server failed to respond: {}", e);
+        }
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Start the bridge HTTP server on 127.0.0.1 and return its port and the
+/// bearer token clients must send. A new token is made on every start;
+/// the existing server's details are returned if it is already running.
+#[tauri::command]
+pub fn start_bridge_server(port: Option<u16>) -> Result<BridgeServerInfo, String> {
+    let mut running = BRIDGE_SERVER.lock();
+    if let Some(server) = running.as_ref() {
+        return Ok(server.info.clone());
+    }
+
+    let server = Server::http(("127.0.0.1", port.unwrap_or(DEFAULT_PORT)))
+        .map_err(|e| format!("Failed to start bridge server: {}", e))?;
+    let port = server
+        .server_addr()
+        .to_ip()
+        .map(|addr| addr.port())
+        .ok_or("Bridge server is not bound to a TCP port")?;
+
+    let info = BridgeServerInfo {
+        port,
+        token: uuid::Uuid::new_v4().simple().to_string(),
+    };
+    let server = Arc::new(server);
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread = {
+        let (server, info, stop) = (server.clone(), info.clone(), stop.clone());
+        std::thread::spawn(move || serve(server, info, stop))
+    };
+
+    tracing::info!("Bridge server listening on 127.0.0.1:{}", port);
+    *running = Some(RunningServer {
+        server,
+        info: info.clone(),
+        stop,
+        thread,
+    });
+    Ok(info)
+}
+
+/// Stop the bridge HTTP server and close open event streams
+#[tauri::command]
+pub fn stop_bridge_server() -> Result<(), String> {
+    let Some(running) = BRIDGE_SERVER.lock().take() else {
+        return Ok(());
+    };
+
+    running.stop.store(true, Ordering::Relaxed);
+    running.server.unblock();
+    running
+        .thread
+        .join()
+        .map_err(|_| "Bridge server thread panicked".to_string())?;
+
+    tracing::info!("Bridge server stopped");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_routes() {
+        assert_eq!(route(&Method::Get, "/bridge/state"), Route::State);
+        assert_eq!(route(&Method::Get, "/bridge/events?x=1"), Route::Events);
+        assert_eq!(route(&Method::Post, "/bridge/approve/abc-1"), Route::Approve("abc-1"));
+        assert_eq!(route(&Method::Post, "/bridge/reject/abc-1/"), Route::Reject("abc-1"));
+        assert_eq!(route(&Method::Post, "/bridge/approve/"), Route::NotFound);
+        assert_eq!(route(&Method::Get, "/bridge/approve/abc-1"), Route::NotFound);
+        assert_eq!(route(&Method::Post, "/bridge/state"), Route::NotFound);
+    }
+
+    fn headers(pairs: &[(&str, &str)]) -> Vec<Header> {
+        pairs
+            .iter()
+            .map(|(name, value)| Header::from_bytes(name.as_bytes(), value.as_bytes()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_access_requires_token() {
+        let ok = headers(&[("Host", "127.0.0.1:7732"), ("Authorization", "Bearer secret")]);
+        assert_eq!(check_access(&ok, 7732, "secret"), Ok(()));
+
+        let localhost = headers(&[("Host", "localhost:7732"), ("Authorization", "Bearer secret")]);
+        assert_eq!(check_access(&localhost, 7732, "secret"), Ok(()));
+
+        let missing = headers(&[("Host", "127.0.0.1:7732")]);
+        assert_eq!(check_access(&missing, 7732, "secret"), Err(401));
+
+        let wrong = headers(&[("Host", "127.0.0.1:7732"), ("Authorization", "Bearer guess")]);
+        assert_eq!(check_access(&wrong, 7732, "secret"), Err(401));
+    }
+
+    #[test]
+    fn test_access_rejects_browser_requests() {
+        // A page posting cross-site carries an Origin, even with no-cors
+        let cross_site = headers(&[
+            ("Host", "127.0.0.1:7732"),
+            ("Origin", "https://evil.example"),
+            ("Authorization", "Bearer secret"),
+        ]);
+        assert_eq!(check_access(&cross_site, 7732, "secret"), Err(403));
+
+        // DNS rebinding reaches us under the attacker's host name
+        let rebound = headers(&[("Host", "evil.example:7732"), ("Authorization", "Bearer secret")]);
+        assert_eq!(check_access(&rebound, 7732, "secret"), Err(403));
+
+        let other_port = headers(&[("Host", "127.0.0.1:8080"), ("Authorization", "Bearer secret")]);
+        assert_eq!(check_access(&other_port, 7732, "secret"), Err(403));
+        assert_eq!(check_access(&[], 7732, "secret"), Err(403));
+    }
+}
@@ -1,9 +1,12 @@
 use chrono::{DateTime, Utc};
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 use tauri::{command, AppHandle, Manager};
 
+use crate::utils::truncate_chars;
+
 /// Single chat message
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
@@ -29,6 +32,9 @@ pub struct ChatSession {
     pub model: Option<String>,
     #[serde(default)]
     pub messages: Vec<ChatMessage>,
+    /// ID of the session this one was forked from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forked_from: Option<String>,
 }
 
 /// Summary of chat session (without messages)
@@ -54,6 +60,7 @@ impl ChatSession {
             message_count: 0,
             model: None,
             messages: Vec::new(),
+            forked_from: None,
         }
     }
 
@@ -80,8 +87,9 @@ impl ChatSession {
             .messages
             .first()
             .map(|m| {
-                if m.content.len() > 100 {
-                    format!("{}...", &m.content[..100])
+                let truncated = truncate_chars(&m.content, 100);
+                if truncated.len() < m.content.len() {
+                    format!("{}...", truncated)
                 } else {
                     m.content.clone()
                 }
@@ -100,6 +108,94 @@ impl ChatSession {
     }
 }
 
+/// Best-matching message of one session for a search query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSearchResult {
+    pub session_id: String,
+    pub session_name: String,
+    pub matching_message_index: u32,
+    pub snippet: String,
+    pub score: f32,
+}
+
+const DEFAULT_SEARCH_LIMIT: u32 = 20;
+/// Characters of context kept on each side of the match in a snippet
+const SNIPPET_CONTEXT_CHARS: usize = 60;
+
+/// Lowercased words of a search query
+fn search_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
+/// Text around the first occurrence of `term`, with `...` where cut
+fn snippet(content: &str, term: &str) -> String {
+    // Lowercasing can turn one character into several (`İ` becomes `i̇`), so
+    // remember which original character each lowercased one came from
+    let mut lower = String::new();
+    let mut origin = Vec::new();
+    for (index, c) in content.chars().enumerate() {
+        for lc in c.to_lowercase() {
+            lower.push(lc);
+            origin.push(index);
+        }
+    }
+
+    let (center, match_end) = lower
+        .find(term)
+        .map(|pos| {
+            let first = lower[..pos].chars().count();
+            let last = first + term.chars().count().max(1) - 1;
+            (origin[first], origin[last] + 1)
+        })
+        .unwrap_or((0, 0));
+    let total = content.chars().count();
+    let start = center.saturating_sub(SNIPPET_CONTEXT_CHARS);
+    let end = (match_end + SNIPPET_CONTEXT_CHARS).min(total);
+
+    let text: String = content.chars().skip(start).take(end.saturating_sub(start)).collect();
+    let text = text.trim().replace('\n', " ");
+    format!(
+        "{}{}{}",
+        if start > 0 { "..." } else { "" },
+        text,
+        if end < total { "..." } else { "" }
+    )
+}
+
+/// Score each message by how often the terms occur in it and keep the best
+fn best_session_match(session: &ChatSession, terms: &[String]) -> Option<SessionSearchResult> {
+    let (index, score, content) = session
+        .messages
+        .iter()
+        .enumerate()
+        .map(|(index, message)| {
+            let lower = message.content.to_lowercase();
+            let score: usize = terms.iter().map(|t| lower.matches(t.as_str()).count()).sum();
+            (index, score, &message.content)
+        })
+        .filter(|(_, score, _)| *score > 0)
+        // On ties keep the earliest message
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))?;
+
+    let lower = content.to_lowercase();
+    let term = terms.iter().find(|t| lower.contains(t.as_str()))?;
+
+    Some(SessionSearchResult {
+        session_id: session.id.clone(),
+        session_name: session.title.clone(),
+        matching_message_index: index as u32,
+        snippet: snippet(content, term),
+        score: score as f32,
+    })
+}
+
 /// Get the chat history directory
 fn get_chat_dir(app: &AppHandle) -> Result<PathBuf, String> {
     let app_data = app
@@ -260,6 +356,112 @@ pub async fn update_chat_title(
     Ok(session)
 }
 
+/// Fork a session at `from_message_index`: the new session keeps the
+/// messages before that index and continues with `new_message`
+#[command]
+pub async fn fork_chat_session(
+    app: AppHandle,
+    session_id: String,
+    from_message_index: u32,
+    new_message: ChatMessage,
+) -> Result<ChatSession, String> {
+    let original = get_chat_session(app.clone(), session_id.clone()).await?;
+
+    let index = from_message_index as usize;
+    if index > original.messages.len() {
+        return Err(format!(
+            "Message index {} out of range (session has {} messages)",
+            index,
+            original.messages.len()
+        ));
+    }
+
+    let mut fork = ChatSession::new(original.title.clone());
+    fork.model = original.model.clone();
+    fork.forked_from = Some(session_id);
+    fork.messages = original.messages[..index].to_vec();
+    fork.messages.push(new_message);
+    fork.message_count = fork.messages.len();
+
+    let chat_dir = get_chat_dir(&app)?;
+    let file_path = chat_dir.join(format!("{}.json", fork.id));
+    let content = serde_json::to_string_pretty(&fork)
+        .map_err(|e| format!("Failed to serialize session: {}", e))?;
+
+    fs::write(&file_path, content)
+        .map_err(|e| format!("Failed to write chat file: {}", e))?;
+
+    Ok(fork)
+}
+
+/// List IDs of sessions forked directly from `session_id`
+#[command]
+pub async fn list_session_forks(app: AppHandle, session_id: String) -> Result<Vec<String>, String> {
+    let chat_dir = get_chat_dir(&app)?;
+    let mut forks: Vec<(DateTime<Utc>, String)> = Vec::new();
+
+    let entries = fs::read_dir(&chat_dir)
+        .map_err(|e| format!("Failed to read chat dir: {}", e))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().map(|e| e == "json").unwrap_or(false) {
+            if let Ok(content) = fs::read_to_string(&path) {
+                if let Ok(session) = serde_json::from_str::<ChatSession>(&content) {
+                    if session.forked_from.as_deref() == Some(session_id.as_str()) {
+                        forks.push((session.created_at, session.id));
+                    }
+                }
+            }
+        }
+    }
+
+    forks.sort();
+    Ok(forks.into_iter().map(|(_, id)| id).collect())
+}
+
+/// Search message contents across all sessions, best match per session,
+/// highest term frequency first. Sessions are loaded in parallel on a
+/// blocking thread so the search does not hold up the async runtime.
+#[command]
+pub async fn search_chat_sessions(
+    app: AppHandle,
+    query: String,
+    limit: Option<u32>,
+) -> Result<Vec<SessionSearchResult>, String> {
+    let terms = search_terms(&query);
+    if terms.is_empty() {
+        return Err("Search query is empty".to_string());
+    }
+
+    let chat_dir = get_chat_dir(&app)?;
+    let mut results = tokio::task::spawn_blocking(move || {
+        let paths: Vec<PathBuf> = fs::read_dir(&chat_dir)
+            .map_err(|e| format!("Failed to read chat dir: {}", e))?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().map(|e| e == "json").unwrap_or(false))
+            .collect();
+
+        Ok::<Vec<SessionSearchResult>, String>(
+            paths
+                .par_iter()
+                .filter_map(|path| {
+                    let content = fs::read_to_string(path).ok()?;
+                    let session = serde_json::from_str::<ChatSession>(&content).ok()?;
+                    best_session_match(&session, &terms)
+                })
+                .collect(),
+        )
+    })
+    .await
+    .map_err(|e| format!("Search failed: {}", e))??;
+
+    results.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.session_id.cmp(&b.session_id)));
+    results.truncate(limit.unwrap_or(DEFAULT_SEARCH_LIMIT) as usize);
+    Ok(results)
+}
+
 /// Clear all chat history
 #[command]
 pub async fn clear_all_chats(app: AppHandle) -> Result<(), String> {
@@ -277,3 +479,44 @@ pub async fn clear_all_chats(app: AppHandle) -> Result<(), String> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_best_session_match() {
+        let mut session = ChatSession::new("Rust questions".to_string());
+        session.add_message("user".to_string(), "How do I use a Mutex?".to_string(), None);
+        session.add_message(
+            "assistant".to_string(),
+            "Wrap the data in a mutex, then lock the mutex before each access.".to_string(),
+            None,
+        );
+
+        let result = best_session_match(&session, &search_terms("MUTEX lock")).unwrap();
+        assert_eq!(result.matching_message_index, 1);
+        assert_eq!(result.score, 3.0);
+        assert_eq!(result.session_name, "Rust questions");
+
+        assert!(best_session_match(&session, &search_terms("tokio")).is_none());
+    }
+
+    #[test]
+    fn test_snippet_marks_cuts() {
+        let content = format!("{}needle{}", "a".repeat(100), "b".repeat(100));
+        let text = snippet(&content, "needle");
+        assert!(text.starts_with("...") && text.ends_with("..."));
+        assert!(text.contains("needle"));
+        assert_eq!(snippet("short needle text", "needle"), "short needle text");
+    }
+
+    #[test]
+    fn test_snippet_after_expanding_lowercase() {
+        // Each `İ` lowercases to two characters, which must not shift the window
+        let content = format!("{}needle{}", "İ".repeat(100), "b".repeat(100));
+        let text = snippet(&content, "needle");
+        let expected = format!("...{}needle{}...", "İ".repeat(60), "b".repeat(60));
+        assert_eq!(text, expected);
+    }
+}
@@ -0,0 +1,298 @@
+//! Heuristic language detection for code snippets
+//!
+//! Documents added to RAG carry no language of their own, so the content is
+//! guessed from, in order: a shebang line, an unmistakable signature
+//! (`<?php`, `<?xml`, valid JSON, ...), and finally keyword and comment-style
+//! scoring. Good enough to tag snippets; not a parser.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CodeLanguageInfo {
+    pub language: String,
+    /// 0.0 - 1.0
+    pub confidence: f32,
+    /// "shebang", "signature", "keywords", "comments", "keywords+comments" or "none"
+    pub detected_via: String,
+    pub mime_type: String,
+}
+
+/// Below this, detection results are not written into document metadata
+pub const LANGUAGE_CONFIDENCE_THRESHOLD: f32 = 0.5;
+
+const PLAINTEXT: &str = "plaintext";
+
+/// Interpreter name in a shebang line -> language
+const SHEBANG_INTERPRETERS: &[(&str, &str)] = &[
+    ("python", "python"),
+    ("node", "javascript"),
+    ("deno", "typescript"),
+    ("bash", "shell"),
+    ("zsh", "shell"),
+    ("sh", "shell"),
+    ("ruby", "ruby"),
+    ("perl", "perl"),
+    ("php", "php"),
+];
+
+/// Distinctive keywords per language; each one found scores its weight once
+#[rustfmt::skip]
+const KEYWORDS: &[(&str, &[(&str, f32)])] = &[
+    ("rust", &[("fn ", 1.0), ("let mut ", 2.0), ("impl ", 1.5), ("pub fn ", 2.0), ("use std::", 2.0), ("#[derive(", 2.0), ("-> Result<", 2.0), ("match ", 0.5), ("&self", 1.5)]),
+    ("python", &[("def ", 1.0), ("elif ", 2.0), ("self.", 0.5), ("__init__", 2.0), ("import ", 0.5), ("print(", 0.5), (" is None", 2.0), ("):\n", 1.5)]),
+    ("javascript", &[("function ", 1.0), ("const ", 0.5), ("=> ", 0.5), ("console.log(", 2.0), ("require(", 1.5), ("module.exports", 2.0), ("===", 1.0), ("document.", 1.0)]),
+    ("typescript", &[("interface ", 1.5), (": string", 2.0), (": number", 2.0), (": boolean", 2.0), ("export type ", 2.0), ("import type ", 2.0), ("const ", 0.5), ("=> ", 0.5), ("===", 1.0)]),
+    ("go", &[("package ", 1.5), ("func ", 1.5), (":= ", 1.5), ("fmt.", 2.0), ("import (", 2.0), ("err != nil", 2.0)]),
+    ("java", &[("public class ", 1.5), ("public static void main", 2.0), ("System.out.", 2.0), ("@Override", 2.0), ("private final ", 1.5), ("import java.", 2.0)]),
+    ("csharp", &[("using System", 2.0), ("namespace ", 1.0), ("Console.Write", 2.0), ("public class ", 1.0), ("{ get; set; }", 2.0)]),
+    ("c", &[("#include <", 1.5), ("int main(", 1.0), ("printf(", 1.5), ("malloc(", 1.5), ("->", 0.5)]),
+    ("cpp", &[("#include <", 1.0), ("std::", 2.0), ("cout <<", 2.0), ("template<", 2.0), ("nullptr", 2.0), ("int main(", 1.0)]),
+    ("shell", &[("echo ", 1.0), ("\nfi", 2.0), ("; then", 2.0), ("$(", 1.0), ("esac", 2.0), ("; do", 2.0)]),
+    ("sql", &[("SELECT ", 1.5), ("INSERT INTO ", 2.0), ("CREATE TABLE ", 2.0), (" FROM ", 1.0), (" WHERE ", 1.0), ("JOIN ", 1.0)]),
+    ("ruby", &[("def ", 0.5), ("puts ", 2.0), ("require '", 2.0), ("attr_accessor", 2.0), (".each do", 2.0), ("\nend", 1.0)]),
+    ("css", &[("{\n  ", 0.5), ("color:", 1.5), ("margin:", 1.5), ("padding:", 1.5), ("@media ", 2.0), ("display:", 1.5)]),
+];
+
+/// Comment styles, scored like keywords
+#[rustfmt::skip]
+const COMMENT_STYLES: &[(&str, &[(&str, f32)])] = &[
+    ("rust", &[("/// ", 1.0), ("//! ", 2.0)]),
+    ("python", &[("\n# ", 0.5), ("\"\"\"", 1.5)]),
+    ("javascript", &[("// ", 0.5), ("/**", 0.5)]),
+    ("typescript", &[("// ", 0.5), ("/**", 0.5)]),
+    ("go", &[("// ", 0.5)]),
+    ("java", &[("/**", 0.5), ("// ", 0.5)]),
+    ("c", &[("/* ", 1.0)]),
+    ("cpp", &[("// ", 0.5)]),
+    ("shell", &[("\n# ", 0.5)]),
+    ("sql", &[("-- ", 1.5)]),
+    ("ruby", &[("\n# ", 0.5)]),
+    ("css", &[("/* ", 0.5)]),
+];
+
+pub fn mime_type(language: &str) -> &'static str {
+    match language {
+        "rust" => "text/x-rust",
+        "python" => "text/x-python",
+        "javascript" => "text/javascript",
+        "typescript" => "application/typescript",
+        "go" => "text/x-go",
+        "java" => "text/x-java",
+        "csharp" => "text/x-csharp",
+        "c" => "text/x-c",
+        "cpp" => "text/x-c++",
+        "shell" => "application/x-sh",
+        "sql" => "application/sql",
+        "ruby" => "text/x-ruby",
+        "perl" => "text/x-perl",
+        "php" => "application/x-httpd-php",
+        "css" => "text/css",
+        "html" => "text/html",
+        "xml" => "application/xml",
+        "json" => "application/json",
+        _ => "text/plain",
+    }
+}
+
+fn info(language: &str, confidence: f32, detected_via: &str) -> CodeLanguageInfo {
+    CodeLanguageInfo {
+        language: language.to_string(),
+        confidence,
+        detected_via: detected_via.to_string(),
+        mime_type: mime_type(language).to_string(),
+    }
+}
+
+fn from_shebang(content: &str) -> Option<&'static str> {
+    let line = content.lines().next()?.strip_prefix("#!")?;
+    // `#!/usr/bin/env python3` names the interpreter last, `#!/bin/bash -e` first
+    let mut words = line.split_whitespace();
+    let program = words.next()?.rsplit('/').next()?;
+    let program = if program == "env" {
+        words.find(|w| !w.starts_with('-'))?
+    } else {
+        program
+    };
+
+    SHEBANG_INTERPRETERS
+        .iter()
+        .find(|(name, _)| program.starts_with(name))
+        .map(|(_, language)| *language)
+}
+
+fn from_signature(content: &str) -> Option<&'static str> {
+    let head = content.trim_start();
+    let lower: String = head.chars().take(64).collect::<String>().to_lowercase();
+
+    if lower.starts_with("<?php") {
+        Some("php")
+    } else if lower.starts_with("<?xml") {
+        Some("xml")
+    } else if lower.starts_with("<!doctype html") || lower.starts_with("<html") {
+        Some("html")
+    } else if (head.starts_with('{') || head.starts_with('['))
+        && serde_json::from_str::<serde_json::Value>(head).is_ok()
+    {
+        Some("json")
+    } else {
+        None
+    }
+}
+
+fn score(content: &str, table: &[(&str, &[(&str, f32)])], language: &str) -> f32 {
+    table
+        .iter()
+        .filter(|(name, _)| *name == language)
+        .flat_map(|(_, patterns)| patterns.iter())
+        .filter(|(pattern, _)| content.contains(pattern))
+        .map(|(_, weight)| weight)
+        .sum()
+}
+
+/// Guess the language of `content`
+pub fn detect(content: &str) -> CodeLanguageInfo {
+    if let Some(language) = from_shebang(content) {
+        return info(language, 0.95, "shebang");
+    }
+    if let Some(language) = from_signature(content) {
+        return info(language, 0.9, "signature");
+    }
+
+    // Leading newline lets "\n# " style patterns match on the first line
+    let content = format!("\n{}", content);
+    let mut scores: Vec<(&str, f32, f32)> = KEYWORDS
+        .iter()
+        .map(|(language, _)| {
+            (
+                *language,
+                score(&content, KEYWORDS, language),
+                score(&content, COMMENT_STYLES, language),
+            )
+        })
+        .filter(|(_, keywords, comments)| keywords + comments > 0.0)
+        .collect();
+    scores.sort_by(|a, b| (b.1 + b.2).total_cmp(&(a.1 + a.2)));
+
+    let Some(&(language, keywords, comments)) = scores.first() else {
+        return info(PLAINTEXT, 0.0, "none");
+    };
+
+    // Confidence grows with the winner's lead over the runner-up and with
+    // how much evidence it has; a single weak hit stays well below 0.5
+    let best = keywords + comments;
+    let runner_up = scores.get(1).map_or(0.0, |s| s.1 + s.2);
+    let lead = (best - runner_up) / best;
+    let evidence = (best / 4.0).min(1.0);
+    let confidence = (0.3 + 0.6 * lead.max(0.25) * evidence).min(0.9);
+
+    let detected_via = match (keywords > 0.0, comments > 0.0) {
+        (true, true) => "keywords+comments",
+        (true, false) => "keywords",
+        _ => "comments",
+    };
+    info(language, confidence, detected_via)
+}
+
+/// Add `language` and `mime_type` to a document's metadata when detection
+/// is confident and the caller did not already set a language
+pub fn tag_metadata(
+    metadata: Option<serde_json::Value>,
+    content: &str,
+) -> Option<serde_json::Value> {
+    if metadata
+        .as_ref()
+        .is_some_and(|m| !m.is_object() || m.get("language").is_some())
+    {
+        return metadata;
+    }
+
+    let detected = detect(content);
+    if detected.language == PLAINTEXT || detected.confidence < LANGUAGE_CONFIDENCE_THRESHOLD {
+        return metadata;
+    }
+
+    let mut metadata = metadata.unwrap_or_else(|| serde_json::json!({}));
+    if let Some(fields) = metadata.as_object_mut() {
+        fields.insert("language".to_string(), detected.language.into());
+        fields.insert("mime_type".to_string(), detected.mime_type.into());
+    }
+    Some(metadata)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub fn detect_code_language(content: String) -> Result<CodeLanguageInfo, String> {
+    if content.trim().is_empty() {
+        return Err("Content is empty".to_string());
+    }
+    Ok(detect(&content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_by_shebang_and_signature() {
+        assert_eq!(
+            detect("#!/usr/bin/env python3\nprint('hi')").language,
+            "python"
+        );
+        assert_eq!(detect("#!/bin/bash -e\necho hi").language, "shell");
+        assert_eq!(
+            detect("#!/usr/bin/env -S deno run\n").language,
+            "typescript"
+        );
+        assert_eq!(detect("<?php echo 1; ?>").detected_via, "signature");
+        assert_eq!(detect("  {\"a\": [1, 2]}").language, "json");
+        assert_eq!(
+            detect("<!DOCTYPE html><html></html>").mime_type,
+            "text/html"
+        );
+    }
+
+    #[test]
+    fn test_detects_by_keywords() {
+        let samples = [
+            ("rust", "use std::fs;\n\n/// Reads it\npub fn read(&self) -> Result<String, Error> {\n    let mut s = String::new();\n}"),
+            ("python", "class Foo:\n    def __init__(self):\n        self.x = None\n    def bar(self):\n        if self.x is None:\n            return 1"),
+            ("go", "package main\n\nimport (\n\t\"fmt\"\n)\n\nfunc main() {\n\tx := 1\n\tfmt.Println(x)\n}"),
+            ("typescript", "export type Id = string;\ninterface User {\n  id: Id;\n  name: string;\n  age: number;\n}"),
+            ("sql", "SELECT id, name\nFROM users u\nJOIN orders o ON o.user_id = u.id\nWHERE u.active = 1"),
+        ];
+        for (language, content) in samples {
+            let detected = detect(content);
+            assert_eq!(detected.language, language, "{}", content);
+            assert!(
+                detected.confidence >= LANGUAGE_CONFIDENCE_THRESHOLD,
+                "{:?}",
+                detected
+            );
+        }
+    }
+
+    #[test]
+    fn test_plain_text() {
+        let detected = detect("Meeting notes: ship the release on Friday.");
+        assert_eq!(detected.language, "plaintext");
+        assert_eq!(detected.detected_via, "none");
+        assert!(detect_code_language("  ".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_tag_metadata() {
+        let code = "package main\n\nfunc main() {\n\tx := 1\n\tfmt.Println(x)\n}";
+        let tagged = tag_metadata(None, code).unwrap();
+        assert_eq!(tagged["language"], "go");
+        assert_eq!(tagged["mime_type"], "text/x-go");
+
+        // A language set by the caller wins
+        let tagged = tag_metadata(Some(serde_json::json!({ "language": "text" })), code).unwrap();
+        assert_eq!(tagged["language"], "text");
+
+        assert_eq!(tag_metadata(None, "just some prose"), None);
+    }
+}
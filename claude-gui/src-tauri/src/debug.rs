@@ -4,7 +4,7 @@
 //! Features:
 //! - System metrics (memory, CPU, tasks)
 //! - IPC call tracking
-//! - Ring buffer log storage
+//! - Ring buffer log storage, fed by `tracing` through [`DebugLogLayer`]
 //! - Event streaming for live updates
 
 use parking_lot::RwLock;
@@ -14,6 +14,8 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tauri::{command, AppHandle, Emitter};
+use tracing::field::{Field, Visit};
+use tracing_subscriber::layer::{Context, Layer};
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // Constants
@@ -232,6 +234,61 @@ macro_rules! debug_error {
     };
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// Tracing Integration
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// `tracing` layer that copies every event into the LiveView log buffer, so
+/// `debug_get_logs` shows the same entries as the log file
+pub struct DebugLogLayer;
+
+/// Collects an event's message and any extra `key=value` fields
+#[derive(Default)]
+struct EventVisitor {
+    message: String,
+    fields: Vec<String>,
+}
+
+impl Visit for EventVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields.push(format!("{}={}", field.name(), value));
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields.push(format!("{}={:?}", field.name(), value));
+        }
+    }
+}
+
+impl<S: tracing::Subscriber> Layer<S> for DebugLogLayer {
+    fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let level = match *metadata.level() {
+            tracing::Level::ERROR => LogLevel::Error,
+            tracing::Level::WARN => LogLevel::Warn,
+            tracing::Level::INFO => LogLevel::Info,
+            _ => LogLevel::Debug,
+        };
+
+        let mut visitor = EventVisitor::default();
+        event.record(&mut visitor);
+
+        let mut message = visitor.message;
+        if !visitor.fields.is_empty() {
+            message = format!("{} {}", message, visitor.fields.join(" ")).trim().to_string();
+        }
+
+        log(level, metadata.target(), &crate::logging::redact_secrets(&message));
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Public API - IPC Tracking (reserved for future use)
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -522,3 +579,25 @@ pub fn init() {
         &format!("CPU cores: {}", num_cpus::get()),
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_tracing_events_reach_ring_buffer() {
+        let subscriber = tracing_subscriber::registry().with(DebugLogLayer);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(target: "debug_layer_test", attempt = 2, "request failed: {}", "timeout");
+            tracing::info!(target: "debug_layer_test", "api_key=abc123");
+        });
+
+        let logs = DEBUG_STATE.logs.read();
+        let captured: Vec<&LogEntry> = logs.iter().filter(|e| e.source == "debug_layer_test").collect();
+        assert_eq!(captured.len(), 2);
+        assert_eq!(captured[0].level, LogLevel::Warn);
+        assert_eq!(captured[0].message, "request failed: timeout attempt=2");
+        assert_eq!(captured[1].message, "api_key=[REDACTED]");
+    }
+}
@@ -0,0 +1,120 @@
+//! `geminicli://` deep links
+//!
+//! URIs like `geminicli://chat?model=X&prompt=Y&session=Z` are validated and
+//! forwarded to the frontend as `deep-link` events. The frontend decides what
+//! each action does; nothing here executes link content.
+
+use reqwest::Url;
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri::{App, AppHandle, Emitter};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+const SCHEME: &str = "geminicli";
+const MAX_PROMPT_CHARS: usize = 2000;
+const MAX_PARAM_CHARS: usize = 256;
+
+/// Characters with special meaning to a shell; links carrying them are rejected
+const SHELL_CHARS: &[char] = &[';', '&', '|', '$', '`', '<', '>', '\\'];
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DeepLinkPayload {
+    pub action: String,
+    pub params: HashMap<String, String>,
+}
+
+fn validate_param(key: &str, value: &str) -> Result<(), String> {
+    let max_chars = if key == "prompt" { MAX_PROMPT_CHARS } else { MAX_PARAM_CHARS };
+    if value.chars().count() > max_chars {
+        return Err(format!("Parameter '{}' exceeds {} characters", key, max_chars));
+    }
+
+    // Newlines are fine in a prompt, other control characters are not
+    let bad_control = |c: char| c.is_control() && !(key == "prompt" && (c == '\n' || c == '\t'));
+    if value.contains(SHELL_CHARS) || value.chars().any(bad_control) {
+        return Err(format!("Parameter '{}' contains disallowed characters", key));
+    }
+
+    Ok(())
+}
+
+/// Parse and validate a `geminicli://<action>?<params>` URI
+pub fn parse_deep_link(uri: &str) -> Result<DeepLinkPayload, String> {
+    let url = Url::parse(uri).map_err(|e| format!("Invalid deep link: {}", e))?;
+
+    if url.scheme() != SCHEME {
+        return Err(format!("Unsupported scheme: {}", url.scheme()));
+    }
+
+    let action = url.host_str().unwrap_or_default().to_lowercase();
+    if action.is_empty() || !action.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("Invalid deep link action: '{}'", action));
+    }
+
+    let mut params = HashMap::new();
+    for (key, value) in url.query_pairs() {
+        if !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("Invalid parameter name: '{}'", key));
+        }
+        validate_param(&key, &value)?;
+        params.insert(key.into_owned(), value.into_owned());
+    }
+
+    Ok(DeepLinkPayload { action, params })
+}
+
+fn handle_urls(app: &AppHandle, urls: impl IntoIterator<Item = Url>) {
+    for url in urls {
+        match parse_deep_link(url.as_str()) {
+            Ok(payload) => {
+                tracing::info!("Deep link: {}", payload.action);
+                let _ = app.emit("deep-link", &payload);
+            }
+            Err(e) => tracing::warn!("Rejected deep link: {}", e),
+        }
+    }
+}
+
+/// Register the URL scheme and route incoming links to the frontend
+pub fn init(app: &App) {
+    // macOS registers the scheme from the bundle; Linux and Windows need it
+    // registered at runtime so development builds receive links too. There a
+    // link starts a new process, which the single-instance plugin (see
+    // `lib.rs`) forwards to this one
+    #[cfg(any(windows, target_os = "linux"))]
+    if let Err(e) = app.deep_link().register_all() {
+        tracing::warn!("Failed to register deep link scheme: {}", e);
+    }
+
+    let handle = app.handle().clone();
+    app.deep_link().on_open_url(move |event| handle_urls(&handle, event.urls()));
+
+    // A link that launched the app arrives before the handler was attached
+    if let Ok(Some(urls)) = app.deep_link().get_current() {
+        handle_urls(app.handle(), urls);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_chat_link() {
+        let payload = parse_deep_link("geminicli://chat?model=llama3.2&prompt=Hello%20world&session=abc-1").unwrap();
+        assert_eq!(payload.action, "chat");
+        assert_eq!(payload.params["model"], "llama3.2");
+        assert_eq!(payload.params["prompt"], "Hello world");
+        assert_eq!(payload.params["session"], "abc-1");
+    }
+
+    #[test]
+    fn test_rejects_invalid_links() {
+        assert!(parse_deep_link("https://chat?prompt=hi").is_err());
+        assert!(parse_deep_link("geminicli://chat?prompt=hi%3B%20rm%20-rf").is_err());
+        assert!(parse_deep_link("geminicli://chat?model=%60id%60").is_err());
+
+        let long_prompt = format!("geminicli://chat?prompt={}", "a".repeat(MAX_PROMPT_CHARS + 1));
+        assert!(parse_deep_link(&long_prompt).is_err());
+    }
+}
@@ -0,0 +1,84 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use thiserror::Error;
+
+/// Error returned by Tauri commands.
+///
+/// Serializes as `{ code, message }` so the frontend can branch on `code`
+/// while still showing `message` to the user.
+#[derive(Debug, Error)]
+pub enum AppError {
+    #[error("Failed to connect to Ollama: {0}")]
+    OllamaUnavailable(String),
+    #[error("Model not found: {0}")]
+    ModelNotFound(String),
+    #[error("Ollama API error: {0}")]
+    OllamaApi(String),
+    #[error("Failed to parse response: {0}")]
+    InvalidResponse(String),
+    #[error("Stream error: {0}")]
+    Stream(String),
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl AppError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::OllamaUnavailable(_) => "ollama_unavailable",
+            AppError::ModelNotFound(_) => "model_not_found",
+            AppError::OllamaApi(_) => "ollama_api",
+            AppError::InvalidResponse(_) => "invalid_response",
+            AppError::Stream(_) => "stream",
+            AppError::InvalidInput(_) => "invalid_input",
+            AppError::Internal(_) => "internal",
+        }
+    }
+}
+
+impl Serialize for AppError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("AppError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::Internal(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serializes_code_and_message() {
+        let value = serde_json::to_value(AppError::ModelNotFound("llama3".to_string())).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({ "code": "model_not_found", "message": "Model not found: llama3" })
+        );
+
+        let value = serde_json::to_value(AppError::Internal("disk full".to_string())).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({ "code": "internal", "message": "disk full" })
+        );
+    }
+
+    #[test]
+    fn test_string_maps_to_internal() {
+        let error = AppError::from("lock poisoned".to_string());
+        assert!(matches!(&error, AppError::Internal(msg) if msg == "lock poisoned"));
+        assert_eq!(error.code(), "internal");
+    }
+}
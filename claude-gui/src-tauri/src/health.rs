@@ -0,0 +1,98 @@
+//! Aggregate health check so the frontend can poll one command instead of
+//! querying each subsystem separately.
+
+use serde::Serialize;
+use std::future::Future;
+use std::time::Duration;
+use tauri::State;
+
+use crate::ollama_commands::OllamaState;
+
+/// Per-check limit; a slow subsystem is reported unhealthy rather than
+/// holding up the whole response
+const CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemHealth {
+    pub ollama_reachable: bool,
+    pub ollama_url: String,
+    pub embedding_model_available: bool,
+    pub model_loaded: bool,
+    /// First model Ollama reports as loaded (`/api/ps`)
+    pub loaded_model: Option<String>,
+    /// Whether a loaded model sits (partly) in VRAM. Only known while a
+    /// model is loaded, so `false` otherwise.
+    pub gpu_available: bool,
+    pub bridge_pending_count: u32,
+    pub memory_entry_count: u32,
+    pub rag_document_count: u32,
+    pub disk_free_gb: f32,
+}
+
+/// Run a check with [`CHECK_TIMEOUT`], falling back to `default` on timeout
+async fn with_timeout<T>(check: impl Future<Output = T>, default: T) -> T {
+    tokio::time::timeout(CHECK_TIMEOUT, check).await.unwrap_or(default)
+}
+
+/// Run blocking filesystem work off the async runtime with [`CHECK_TIMEOUT`]
+async fn blocking_with_timeout<T: Send + 'static>(
+    check: impl FnOnce() -> T + Send + 'static,
+    default: T,
+) -> T {
+    match tokio::time::timeout(CHECK_TIMEOUT, tokio::task::spawn_blocking(check)).await {
+        Ok(Ok(value)) => value,
+        _ => default,
+    }
+}
+
+fn bridge_pending_count() -> u32 {
+    crate::bridge::get_bridge_state()
+        .map(|data| data.requests.iter().filter(|r| r.status == "pending").count() as u32)
+        .unwrap_or(0)
+}
+
+fn disk_free_gb() -> f32 {
+    let path = dirs::data_local_dir().unwrap_or_else(|| std::path::PathBuf::from("."));
+    fs2::available_space(&path)
+        .map(|bytes| bytes as f32 / 1024.0 / 1024.0 / 1024.0)
+        .unwrap_or(0.0)
+}
+
+/// Checks are independent, so they run concurrently and the response takes
+/// roughly as long as the slowest one (at most [`CHECK_TIMEOUT`])
+#[tauri::command]
+pub async fn get_system_health(state: State<'_, OllamaState>) -> Result<SystemHealth, String> {
+    let client = state.client.read().await;
+    let ollama_url = client.base_url().to_string();
+
+    let (
+        ollama_reachable,
+        embedding_model_available,
+        running_models,
+        bridge_pending_count,
+        memory_entry_count,
+        rag_document_count,
+        disk_free_gb,
+    ) = tokio::join!(
+        with_timeout(async { client.health_check().await.unwrap_or(false) }, false),
+        with_timeout(crate::learning::check_embedding_model(), false),
+        with_timeout(async { client.running_models().await.unwrap_or_default() }, Vec::new()),
+        blocking_with_timeout(bridge_pending_count, 0),
+        blocking_with_timeout(crate::memory::memory_entry_count, 0),
+        blocking_with_timeout(crate::learning::rag_document_count, 0),
+        blocking_with_timeout(disk_free_gb, 0.0),
+    );
+
+    Ok(SystemHealth {
+        ollama_reachable,
+        ollama_url,
+        embedding_model_available,
+        model_loaded: !running_models.is_empty(),
+        loaded_model: running_models.first().map(|m| m.name.clone()),
+        gpu_available: running_models.iter().any(|m| m.size_vram > 0),
+        bridge_pending_count,
+        memory_entry_count,
+        rag_document_count,
+        disk_free_gb,
+    })
+}
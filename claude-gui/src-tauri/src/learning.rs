@@ -1,8 +1,17 @@
+use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::io::Write;
 use std::path::PathBuf;
 use std::process::Command;
+use std::sync::Arc;
+use tauri::{Emitter, Window};
+
+use crate::code_language;
+use crate::error::AppError;
+use crate::ollama::ndjson::{parse_line, NdjsonReader};
+use crate::utils::truncate_chars;
 
 // ============================================================================
 // Types
@@ -44,14 +53,123 @@ impl Default for UserPreferences {
     }
 }
 
+/// Service that turns text into embedding vectors for the RAG store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum EmbeddingProvider {
+    Ollama {
+        url: String,
+        model: String,
+        /// Context size of the embedding model; longer input is chunked
+        #[serde(default = "default_max_input_tokens")]
+        max_input_tokens: usize,
+    },
+    /// Any OpenAI-compatible `/v1/embeddings` endpoint
+    OpenAI {
+        api_base: String,
+        /// Kept in the user config dir (see `get_api_key_path`), never in
+        /// `embedding_provider.json` and never sent back to the UI
+        #[serde(default, skip_serializing)]
+        api_key: String,
+        model: String,
+        #[serde(default = "default_max_input_tokens")]
+        max_input_tokens: usize,
+    },
+}
+
+/// The current provider as shown to the UI: the API key itself is left out
+#[derive(Debug, Clone, Serialize)]
+pub struct EmbeddingProviderView {
+    #[serde(flatten)]
+    pub provider: EmbeddingProvider,
+    pub has_api_key: bool,
+}
+
+/// Context size of mxbai-embed-large, the default model
+fn default_max_input_tokens() -> usize {
+    512
+}
+
+impl Default for EmbeddingProvider {
+    fn default() -> Self {
+        Self::Ollama {
+            url: std::env::var("OLLAMA_URL").unwrap_or_else(|_| "http://localhost:11434".to_string()),
+            model: "mxbai-embed-large".to_string(),
+            max_input_tokens: default_max_input_tokens(),
+        }
+    }
+}
+
+impl EmbeddingProvider {
+    fn max_input_tokens(&self) -> usize {
+        match self {
+            Self::Ollama { max_input_tokens, .. } | Self::OpenAI { max_input_tokens, .. } => *max_input_tokens,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RagDocument {
     pub id: String,
     pub content: String,
     pub score: Option<f64>,
     pub metadata: Option<serde_json::Value>,
+    /// Where the content came from, e.g. a file path or URL
+    pub source: String,
+    /// e.g. "file", "url" or "manual"
+    pub source_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceSummary {
+    pub source: String,
+    pub source_type: Option<String>,
+    pub document_count: u32,
+}
+
+/// Source reported for documents added before provenance was recorded
+const UNKNOWN_SOURCE: &str = "unknown";
+
+/// Input document for [`learning_rag_reindex`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RagReindexDoc {
+    pub id: String,
+    pub content: String,
+    pub source: String,
+    pub source_type: Option<String>,
+    pub metadata: Option<serde_json::Value>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RagReindexFailure {
+    pub id: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RagReindexSummary {
+    pub succeeded: u32,
+    pub failed: u32,
+    /// Already indexed by an earlier, interrupted run
+    pub skipped: u32,
+    pub cancelled: bool,
+    pub failures: Vec<RagReindexFailure>,
+}
+
+/// Payload of the `rag-reindex-progress` event
+#[derive(Debug, Clone, Serialize)]
+pub struct RagReindexProgress {
+    pub collection: String,
+    pub processed: u32,
+    pub total: u32,
+    pub succeeded: u32,
+    pub failed: u32,
+}
+
+/// Documents embedded concurrently per reindex batch; each batch is written
+/// to disk before the next starts
+const REINDEX_BATCH_SIZE: usize = 8;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrainingExample {
     pub instruction: String,
@@ -60,13 +178,52 @@ pub struct TrainingExample {
     pub collected_at: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationError {
+    pub file: String,
+    pub line: u32,
+    pub field: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub total_lines: u32,
+    pub valid: u32,
+    pub invalid: u32,
+    /// Capped at [`MAX_VALIDATION_ERRORS`]; counts above still cover every line
+    pub errors: Vec<ValidationError>,
+}
+
+/// Fine-tuning dataset layout
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// `{instruction, input, output}`
+    #[default]
+    Alpaca,
+    /// `{conversations: [{from: "human"}, {from: "gpt"}]}`
+    ShareGPT,
+    /// `{messages: [{role: "user"}, {role: "assistant"}]}`
+    OpenAI,
+}
+
+/// Number of instruction examples collected on one day
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DayBucket {
+    pub date: String,
+    pub count: u32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExportResult {
+    pub format: ExportFormat,
     pub train_path: String,
     pub eval_path: String,
     pub train_count: u32,
     pub eval_count: u32,
-    pub notebook_path: String,
+    /// Only produced for the Alpaca export
+    pub notebook_path: Option<String>,
 }
 
 // ============================================================================
@@ -105,6 +262,41 @@ fn get_vectors_dir() -> PathBuf {
     path
 }
 
+fn get_embedding_provider_path() -> PathBuf {
+    let mut path = get_data_dir();
+    path.push("embedding_provider.json");
+    path
+}
+
+/// Outside the working tree, so running from a checkout cannot commit it
+fn get_api_key_path() -> PathBuf {
+    let mut path = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("claude-cli");
+    path.push("embedding_api_key");
+    path
+}
+
+/// Write a secret readable by the current user only
+fn write_secret(path: &std::path::Path, secret: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut file = options.open(path).map_err(|e| format!("Failed to write API key: {}", e))?;
+    // `mode` only applies to new files
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600)).map_err(|e| e.to_string())?;
+    }
+    file.write_all(secret.as_bytes()).map_err(|e| format!("Failed to write API key: {}", e))
+}
+
 fn get_preferences_path() -> PathBuf {
     let mut path = get_data_dir();
     path.push("preferences.json");
@@ -112,58 +304,389 @@ fn get_preferences_path() -> PathBuf {
 }
 
 // ============================================================================
-// Ollama Embedding API
+// Embedding API
 // ============================================================================
 
+lazy_static::lazy_static! {
+    static ref EMBEDDING_PROVIDER: RwLock<EmbeddingProvider> = RwLock::new(load_embedding_provider());
+}
+
+fn load_embedding_provider() -> EmbeddingProvider {
+    let mut provider: EmbeddingProvider = fs::read_to_string(get_embedding_provider_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+
+    if let EmbeddingProvider::OpenAI { api_key, .. } = &mut provider {
+        if api_key.is_empty() {
+            *api_key = fs::read_to_string(get_api_key_path())
+                .map(|key| key.trim().to_string())
+                .unwrap_or_default();
+        } else if let Err(e) = save_embedding_provider(&provider) {
+            // Older versions stored the key in embedding_provider.json
+            tracing::warn!("Failed to move embedding API key out of the data dir: {}", e);
+        }
+    }
+
+    provider
+}
+
+/// Persist the provider, with an OpenAI key going to its own private file
+fn save_embedding_provider(provider: &EmbeddingProvider) -> Result<(), String> {
+    if let EmbeddingProvider::OpenAI { api_key, .. } = provider {
+        write_secret(&get_api_key_path(), api_key)?;
+    }
+
+    let content = serde_json::to_string_pretty(provider).map_err(|e| e.to_string())?;
+    fs::write(get_embedding_provider_path(), content).map_err(|e| e.to_string())
+}
+
+/// Longest part of a response body quoted in an embedding error
+const ERROR_SNIPPET_CHARS: usize = 200;
+
+fn body_snippet(body: &str) -> &str {
+    truncate_chars(body.trim(), ERROR_SNIPPET_CHARS)
+}
+
+/// Read one vector, rejecting empty vectors and non-numeric entries
+fn parse_vector(value: &serde_json::Value) -> Option<Vec<f64>> {
+    let values = value.as_array().filter(|v| !v.is_empty())?;
+    values.iter().map(|v| v.as_f64()).collect()
+}
+
+/// Pull the vectors out of an embedding response, one per input. Accepts:
+///
+/// - Ollama `/api/embed`: `{"embeddings": [[...], ...]}`, or a flat
+///   `{"embeddings": [...]}` from some versions for single input
+/// - Ollama `/api/embeddings` (legacy): `{"embedding": [...]}`
+/// - OpenAI: `{"data": [{"embedding": [...]}, ...]}`
+fn parse_embeddings_response(data: &serde_json::Value) -> Result<Vec<Vec<f64>>, String> {
+    let vectors = if let Some(embeddings) = data["embeddings"].as_array() {
+        if embeddings.first().is_some_and(|v| v.is_array()) {
+            embeddings.iter().map(parse_vector).collect()
+        } else {
+            parse_vector(&data["embeddings"]).map(|v| vec![v])
+        }
+    } else if data["embedding"].is_array() {
+        parse_vector(&data["embedding"]).map(|v| vec![v])
+    } else if let Some(items) = data["data"].as_array() {
+        items.iter().map(|item| parse_vector(&item["embedding"])).collect()
+    } else {
+        None
+    };
+
+    match vectors {
+        Some(vectors) if !vectors.is_empty() => Ok(vectors),
+        _ => Err(format!(
+            "No valid embedding in response: {}",
+            body_snippet(&data.to_string())
+        )),
+    }
+}
+
+/// First vector of an embedding response
+#[cfg(test)]
+fn parse_embedding_response(data: &serde_json::Value) -> Result<Vec<f64>, String> {
+    parse_embeddings_response(data).map(|mut vectors| vectors.swap_remove(0))
+}
+
+/// Rough token size used to turn `max_input_tokens` into a character budget.
+/// Deliberately below the usual ~4 for English, since code and non-English
+/// text tokenize denser and overrunning the model's context silently
+/// truncates the chunk.
+const CHARS_PER_TOKEN: usize = 3;
+/// Upper bound on chunks per text; anything beyond is dropped
+const MAX_EMBEDDING_CHUNKS: usize = 32;
+
+/// Split `text` into pieces of at most `max_chars` characters, breaking at
+/// whitespace when there is some in the second half of the piece
+fn split_for_embedding(text: &str, max_chars: usize) -> Vec<&str> {
+    let max_chars = max_chars.max(1);
+    let mut chunks = Vec::new();
+    let mut rest = text.trim();
+
+    while !rest.is_empty() {
+        let end = rest.char_indices().nth(max_chars).map_or(rest.len(), |(i, _)| i);
+        let split = if end < rest.len() {
+            rest[..end]
+                .rfind(char::is_whitespace)
+                .filter(|&i| i > end / 2)
+                .unwrap_or(end)
+        } else {
+            end
+        };
+        chunks.push(rest[..split].trim_end());
+        rest = rest[split..].trim_start();
+    }
+
+    chunks
+}
+
+/// Combine chunk vectors into one by averaging, weighted by chunk length so
+/// a short trailing chunk counts for less than a full one. The result is
+/// not renormalized: cosine similarity ignores magnitude.
+fn mean_pool(vectors: &[Vec<f64>], weights: &[usize]) -> Result<Vec<f64>, String> {
+    let dimension = vectors.first().map(|v| v.len()).ok_or("No vectors to pool")?;
+    if vectors.iter().any(|v| v.len() != dimension) {
+        return Err("Chunk embeddings have different dimensions".to_string());
+    }
+
+    let total = weights.iter().sum::<usize>().max(1) as f64;
+    let mut pooled = vec![0.0; dimension];
+    for (vector, &weight) in vectors.iter().zip(weights) {
+        let weight = weight as f64 / total;
+        for (sum, x) in pooled.iter_mut().zip(vector) {
+            *sum += x * weight;
+        }
+    }
+    Ok(pooled)
+}
+
+/// Embed `text` as one vector. Text longer than the model's input window is
+/// split into chunks that are embedded in one batch request and mean-pooled
+/// (see [`mean_pool`]), so long documents keep their tail instead of losing it.
 async fn get_embedding(text: &str) -> Result<Vec<f64>, String> {
     let client = reqwest::Client::new();
-    let ollama_url = std::env::var("OLLAMA_URL").unwrap_or_else(|_| "http://localhost:11434".to_string());
+    let provider = EMBEDDING_PROVIDER.read().clone();
+
+    let mut chunks = split_for_embedding(text, provider.max_input_tokens() * CHARS_PER_TOKEN);
+    if chunks.len() > MAX_EMBEDDING_CHUNKS {
+        tracing::warn!(
+            "Embedding input split into {} chunks; only the first {} are used",
+            chunks.len(),
+            MAX_EMBEDDING_CHUNKS
+        );
+        chunks.truncate(MAX_EMBEDDING_CHUNKS);
+    }
+    // Empty text still gets a vector, as before
+    let input = if chunks.is_empty() { vec![""] } else { chunks };
+
+    let request = match &provider {
+        EmbeddingProvider::Ollama { url, model, .. } => client
+            .post(format!("{}/api/embed", url.trim_end_matches('/')))
+            .json(&serde_json::json!({ "model": model, "input": input })),
+        EmbeddingProvider::OpenAI { api_base, api_key, model, .. } => client
+            .post(format!("{}/v1/embeddings", api_base.trim_end_matches('/')))
+            .bearer_auth(api_key)
+            .json(&serde_json::json!({ "model": model, "input": input })),
+    };
 
-    let response = client
-        .post(format!("{}/api/embed", ollama_url))
-        .json(&serde_json::json!({
-            "model": "mxbai-embed-large",
-            "input": text.chars().take(8192).collect::<String>()
-        }))
+    let response = request
         .timeout(std::time::Duration::from_secs(30))
         .send()
         .await
         .map_err(|e| format!("Embedding request failed: {}", e))?;
 
-    if !response.status().is_success() {
-        return Err(format!("Embedding failed: {}", response.status()));
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read embedding response: {}", e))?;
+
+    if !status.is_success() {
+        return Err(format!("Embedding failed ({}): {}", status, body_snippet(&body)));
     }
 
-    let data: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse embedding: {}", e))?;
+    let data: serde_json::Value = serde_json::from_str(&body)
+        .map_err(|e| format!("Failed to parse embedding ({}): {}: {}", status, e, body_snippet(&body)))?;
 
-    let embedding = data["embeddings"][0]
-        .as_array()
-        .or_else(|| data["embedding"].as_array())
-        .ok_or("No embedding in response")?
-        .iter()
-        .filter_map(|v| v.as_f64())
-        .collect();
+    let vectors = parse_embeddings_response(&data).map_err(|e| format!("{} ({})", e, status))?;
+    if vectors.len() != input.len() {
+        return Err(format!(
+            "Expected {} embeddings, got {}",
+            input.len(),
+            vectors.len()
+        ));
+    }
 
-    Ok(embedding)
+    let weights: Vec<usize> = input.iter().map(|chunk| chunk.chars().count()).collect();
+    mean_pool(&vectors, &weights)
 }
 
-fn cosine_similarity(a: &[f64], b: &[f64]) -> f64 {
+/// Dot product summed in 8-wide lanes. Independent accumulators let the
+/// compiler vectorize the loop, which a single running sum prevents.
+fn dot_product(a: &[f64], b: &[f64]) -> f64 {
+    let mut lanes = [0.0f64; 8];
+    let chunks_a = a.chunks_exact(8);
+    let chunks_b = b.chunks_exact(8);
+    let (rem_a, rem_b) = (chunks_a.remainder(), chunks_b.remainder());
+
+    for (ca, cb) in chunks_a.zip(chunks_b) {
+        for ((lane, x), y) in lanes.iter_mut().zip(ca).zip(cb) {
+            *lane += x * y;
+        }
+    }
+
+    let tail: f64 = rem_a.iter().zip(rem_b).map(|(x, y)| x * y).sum();
+    lanes.iter().sum::<f64>() + tail
+}
+
+fn vector_norm(v: &[f64]) -> f64 {
+    dot_product(v, v).sqrt()
+}
+
+/// Cosine similarity with both norms already known, so a search only
+/// pays for one dot product per document
+fn cosine_similarity_with_norms(a: &[f64], norm_a: f64, b: &[f64], norm_b: f64) -> f64 {
     if a.len() != b.len() || a.is_empty() {
         return 0.0;
     }
 
-    let dot_product: f64 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
-    let norm_a: f64 = a.iter().map(|x| x * x).sum::<f64>().sqrt();
-    let norm_b: f64 = b.iter().map(|x| x * x).sum::<f64>().sqrt();
-
     if norm_a == 0.0 || norm_b == 0.0 {
         return 0.0;
     }
 
-    dot_product / (norm_a * norm_b)
+    dot_product(a, b) / (norm_a * norm_b)
+}
+
+#[cfg(test)]
+fn cosine_similarity(a: &[f64], b: &[f64]) -> f64 {
+    cosine_similarity_with_norms(a, vector_norm(a), b, vector_norm(b))
+}
+
+const MAX_VALIDATION_ERRORS: usize = 50;
+const MIN_OUTPUT_CHARS: usize = 10;
+
+/// First problem found in one training line, as `(field, message)`
+fn check_training_line(raw: &[u8]) -> Result<(), (&'static str, String)> {
+    let line = std::str::from_utf8(raw).map_err(|e| ("", format!("Invalid UTF-8: {}", e)))?;
+    if line.contains('\0') {
+        return Err(("", "Contains null bytes".to_string()));
+    }
+
+    let value: serde_json::Value = serde_json::from_str(line).map_err(|e| ("", format!("Invalid JSON: {}", e)))?;
+
+    for field in ["instruction", "output"] {
+        match value.get(field) {
+            None | Some(serde_json::Value::Null) => return Err((field, "Missing required field".to_string())),
+            Some(serde_json::Value::String(text)) if text.trim().is_empty() => {
+                return Err((field, "Must not be empty".to_string()))
+            }
+            Some(serde_json::Value::String(_)) => {}
+            Some(_) => return Err((field, "Must be a string".to_string())),
+        }
+    }
+
+    let output_chars = value["output"].as_str().unwrap_or_default().chars().count();
+    if output_chars < MIN_OUTPUT_CHARS {
+        return Err((
+            "output",
+            format!("Must be at least {} characters, got {}", MIN_OUTPUT_CHARS, output_chars),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Validate every non-empty line of one JSONL file into `report`
+fn validate_training_file(file: &str, bytes: &[u8], report: &mut ValidationReport) {
+    for (index, raw) in bytes.split(|b| *b == b'\n').enumerate() {
+        let raw = raw.strip_suffix(b"\r").unwrap_or(raw);
+        if raw.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+
+        report.total_lines += 1;
+        match check_training_line(raw) {
+            Ok(()) => report.valid += 1,
+            Err((field, message)) => {
+                report.invalid += 1;
+                if report.errors.len() < MAX_VALIDATION_ERRORS {
+                    report.errors.push(ValidationError {
+                        file: file.to_string(),
+                        line: index as u32 + 1,
+                        field: field.to_string(),
+                        message,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Path of a named vector store; `None` is the default store
+fn vector_store_path(store_name: Option<&str>) -> Result<PathBuf, String> {
+    let name = store_name.unwrap_or("default");
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("Invalid store name: '{}'", name));
+    }
+    Ok(get_vectors_dir().join(format!("{}.json", name)))
+}
+
+fn rag_document_json(
+    id: &str,
+    content: &str,
+    embedding: Vec<f64>,
+    source: &str,
+    source_type: Option<&str>,
+    metadata: Option<serde_json::Value>,
+) -> serde_json::Value {
+    serde_json::json!({
+        "id": id,
+        "content": content,
+        "norm": vector_norm(&embedding),
+        "embedding": embedding,
+        "source": source,
+        "source_type": source_type,
+        "metadata": metadata.unwrap_or(serde_json::Value::Null),
+        "created_at": chrono::Utc::now().to_rfc3339()
+    })
+}
+
+/// Insert documents into a store, replacing any with the same ID
+fn upsert_documents(store: &mut serde_json::Value, new_docs: Vec<serde_json::Value>) {
+    if !store["documents"].is_array() {
+        store["documents"] = serde_json::json!([]);
+    }
+    if let Some(docs) = store["documents"].as_array_mut() {
+        let ids: HashSet<&str> = new_docs.iter().filter_map(|d| d["id"].as_str()).collect();
+        docs.retain(|d| !d["id"].as_str().is_some_and(|id| ids.contains(id)));
+        docs.extend(new_docs);
+    }
+}
+
+/// Embedding length the store was built with: the `dimension` header, or the
+/// first document's embedding for stores written before the header existed
+fn store_dimension(store: &serde_json::Value) -> Option<usize> {
+    store["dimension"].as_u64().map(|d| d as usize).or_else(|| {
+        store["documents"][0]["embedding"]
+            .as_array()
+            .map(|embedding| embedding.len())
+    })
+}
+
+fn dimension_mismatch(expected: usize, actual: usize) -> String {
+    format!(
+        "This vector store was built with a different embedding model ({}-dimensional vectors, \
+         the current model produces {}). Switch back to that model or rebuild the store with \
+         learning_rag_reembed.",
+        expected, actual
+    )
+}
+
+/// Refuse vectors whose length differs from the store's; record the
+/// dimension in the header of stores that don't have one yet
+fn ensure_store_dimension(store: &mut serde_json::Value, dimension: usize) -> Result<(), String> {
+    match store_dimension(store) {
+        Some(expected) if expected != dimension => Err(dimension_mismatch(expected, dimension)),
+        _ => {
+            store["dimension"] = serde_json::json!(dimension);
+            Ok(())
+        }
+    }
+}
+
+/// IDs already written by an interrupted reindex of the store at `store_path`
+fn reindex_checkpoint_path(store_path: &std::path::Path) -> PathBuf {
+    store_path.with_extension("reindex")
+}
+
+fn load_vector_store(path: &std::path::Path) -> Result<serde_json::Value, String> {
+    if !path.exists() {
+        return Ok(serde_json::json!({ "version": 1, "documents": [] }));
+    }
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| e.to_string())
 }
 
 // ============================================================================
@@ -222,12 +745,23 @@ pub async fn learning_get_stats() -> Result<LearningStats, String> {
     })
 }
 
-async fn check_embedding_model() -> bool {
-    let client = reqwest::Client::new();
-    let ollama_url = std::env::var("OLLAMA_URL").unwrap_or_else(|_| "http://localhost:11434".to_string());
+/// Number of documents in the RAG vector store
+pub(crate) fn rag_document_count() -> u32 {
+    let content = fs::read_to_string(get_vectors_dir().join("default.json")).unwrap_or_default();
+    let data: serde_json::Value = serde_json::from_str(&content).unwrap_or_default();
+    data["documents"].as_array().map(|a| a.len()).unwrap_or(0) as u32
+}
 
+pub(crate) async fn check_embedding_model() -> bool {
+    let (url, model) = match EMBEDDING_PROVIDER.read().clone() {
+        EmbeddingProvider::Ollama { url, model, .. } => (url, model),
+        // No cheap availability check; a configured key is the best signal
+        EmbeddingProvider::OpenAI { api_key, .. } => return !api_key.is_empty(),
+    };
+
+    let client = reqwest::Client::new();
     let response = client
-        .get(format!("{}/api/tags", ollama_url))
+        .get(format!("{}/api/tags", url.trim_end_matches('/')))
         .timeout(std::time::Duration::from_secs(5))
         .send()
         .await;
@@ -238,7 +772,7 @@ async fn check_embedding_model() -> bool {
                 return models.iter().any(|m| {
                     m["name"]
                         .as_str()
-                        .map(|n| n.contains("mxbai-embed") || n.contains("nomic-embed"))
+                        .map(|n| n == model || n.starts_with(&format!("{}:", model)))
                         .unwrap_or(false)
                 });
             }
@@ -294,6 +828,14 @@ pub async fn learning_rag_search(query: String, top_k: Option<u32>) -> Result<Ve
 
     // Get query embedding
     let query_embedding = get_embedding(&query).await?;
+    let query_norm = vector_norm(&query_embedding);
+
+    // Mismatched lengths would score every document 0 and return nothing
+    if let Some(expected) = store_dimension(&data) {
+        if expected != query_embedding.len() {
+            return Err(dimension_mismatch(expected, query_embedding.len()));
+        }
+    }
 
     // Calculate similarities
     let mut results: Vec<(f64, &serde_json::Value)> = documents
@@ -305,7 +847,9 @@ pub async fn learning_rag_search(query: String, top_k: Option<u32>) -> Result<Ve
                 .filter_map(|v| v.as_f64())
                 .collect();
 
-            let score = cosine_similarity(&query_embedding, &embedding);
+            // Documents stored before norms were precomputed fall back to computing it here
+            let doc_norm = doc["norm"].as_f64().unwrap_or_else(|| vector_norm(&embedding));
+            let score = cosine_similarity_with_norms(&query_embedding, query_norm, &embedding, doc_norm);
             if score > 0.5 {
                 Some((score, doc))
             } else {
@@ -326,6 +870,8 @@ pub async fn learning_rag_search(query: String, top_k: Option<u32>) -> Result<Ve
             content: doc["content"].as_str().unwrap_or("").to_string(),
             score: Some(score),
             metadata: doc.get("metadata").cloned(),
+            source: doc["source"].as_str().unwrap_or(UNKNOWN_SOURCE).to_string(),
+            source_type: doc["source_type"].as_str().map(str::to_string),
         })
         .collect();
 
@@ -333,7 +879,20 @@ pub async fn learning_rag_search(query: String, top_k: Option<u32>) -> Result<Ve
 }
 
 #[tauri::command]
-pub async fn learning_rag_add(id: String, content: String, metadata: Option<serde_json::Value>) -> Result<bool, String> {
+pub async fn learning_rag_add(
+    id: String,
+    content: String,
+    source: String,
+    source_type: Option<String>,
+    metadata: Option<serde_json::Value>,
+) -> Result<bool, String> {
+    if source.trim().is_empty() {
+        return Err("Document source is required".to_string());
+    }
+
+    // Tag code with its language so searches can be filtered by it
+    let metadata = code_language::tag_metadata(metadata, &content);
+
     // Get embedding
     let embedding = get_embedding(&content).await?;
 
@@ -354,20 +913,11 @@ pub async fn learning_rag_add(id: String, content: String, metadata: Option<serd
         })
     };
 
-    // Add document
-    let doc = serde_json::json!({
-        "id": id,
-        "content": content,
-        "embedding": embedding,
-        "metadata": metadata.unwrap_or(serde_json::Value::Null),
-        "created_at": chrono::Utc::now().to_rfc3339()
-    });
+    ensure_store_dimension(&mut store, embedding.len())?;
 
-    if let Some(docs) = store["documents"].as_array_mut() {
-        // Remove existing doc with same ID
-        docs.retain(|d| d["id"].as_str() != Some(&id));
-        docs.push(doc);
-    }
+    // Add document, replacing any existing doc with the same ID
+    let doc = rag_document_json(&id, &content, embedding, &source, source_type.as_deref(), metadata);
+    upsert_documents(&mut store, vec![doc]);
 
     // Save
     let content = serde_json::to_string(&store).map_err(|e| e.to_string())?;
@@ -376,6 +926,235 @@ pub async fn learning_rag_add(id: String, content: String, metadata: Option<serd
     Ok(true)
 }
 
+/// Documents grouped by source, most documents first
+#[tauri::command]
+pub fn learning_rag_list_sources(store_name: Option<String>) -> Result<Vec<SourceSummary>, String> {
+    let store = load_vector_store(&vector_store_path(store_name.as_deref())?)?;
+    let mut sources: HashMap<String, SourceSummary> = HashMap::new();
+
+    for doc in store["documents"].as_array().into_iter().flatten() {
+        let source = doc["source"].as_str().unwrap_or(UNKNOWN_SOURCE);
+        let summary = sources.entry(source.to_string()).or_insert_with(|| SourceSummary {
+            source: source.to_string(),
+            source_type: doc["source_type"].as_str().map(str::to_string),
+            document_count: 0,
+        });
+        summary.document_count += 1;
+    }
+
+    let mut sources: Vec<SourceSummary> = sources.into_values().collect();
+    sources.sort_by(|a, b| b.document_count.cmp(&a.document_count).then_with(|| a.source.cmp(&b.source)));
+    Ok(sources)
+}
+
+/// Remove every document from `source`, returning how many were deleted
+#[tauri::command]
+pub fn learning_rag_delete_by_source(source: String, store_name: Option<String>) -> Result<u32, String> {
+    let path = vector_store_path(store_name.as_deref())?;
+    if !path.exists() {
+        return Ok(0);
+    }
+
+    let mut store = load_vector_store(&path)?;
+    let Some(docs) = store["documents"].as_array_mut() else {
+        return Ok(0);
+    };
+
+    let before = docs.len();
+    docs.retain(|d| d["source"].as_str().unwrap_or(UNKNOWN_SOURCE) != source);
+    let removed = (before - docs.len()) as u32;
+
+    if removed > 0 {
+        let content = serde_json::to_string(&store).map_err(|e| e.to_string())?;
+        fs::write(&path, content).map_err(|e| e.to_string())?;
+    }
+
+    Ok(removed)
+}
+
+lazy_static::lazy_static! {
+    /// Collections whose running reindex should stop after the current batch
+    static ref REINDEX_CANCELLED: RwLock<HashSet<String>> = RwLock::new(HashSet::new());
+}
+
+/// Embed and store `docs` in `collection` in batches of [`REINDEX_BATCH_SIZE`].
+///
+/// Each batch is saved before the next one starts and its IDs are added to a
+/// checkpoint, so re-running after a crash or cancel skips finished documents.
+/// The checkpoint is removed once a run completes without failures.
+#[tauri::command]
+pub async fn learning_rag_reindex(
+    window: Window,
+    collection: Option<String>,
+    docs: Vec<RagReindexDoc>,
+) -> Result<RagReindexSummary, String> {
+    use futures_util::future::join_all;
+
+    let collection_name = collection.unwrap_or_else(|| "default".to_string());
+    let store_path = vector_store_path(Some(&collection_name))?;
+    let checkpoint_path = reindex_checkpoint_path(&store_path);
+
+    let mut done: HashSet<String> = fs::read_to_string(&checkpoint_path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+
+    REINDEX_CANCELLED.write().remove(&collection_name);
+
+    let total = docs.len() as u32;
+    let mut summary = RagReindexSummary::default();
+    let (pending, skipped): (Vec<_>, Vec<_>) = docs.into_iter().partition(|d| !done.contains(&d.id));
+    summary.skipped = skipped.len() as u32;
+
+    for batch in pending.chunks(REINDEX_BATCH_SIZE) {
+        if REINDEX_CANCELLED.read().contains(&collection_name) {
+            summary.cancelled = true;
+            break;
+        }
+
+        let embeddings = join_all(batch.iter().map(|doc| async move {
+            if doc.source.trim().is_empty() {
+                return Err("Document source is required".to_string());
+            }
+            get_embedding(&doc.content).await
+        }))
+        .await;
+
+        let mut new_docs = Vec::with_capacity(batch.len());
+        for (doc, embedding) in batch.iter().zip(embeddings) {
+            match embedding {
+                Ok(embedding) => new_docs.push(rag_document_json(
+                    &doc.id,
+                    &doc.content,
+                    embedding,
+                    &doc.source,
+                    doc.source_type.as_deref(),
+                    code_language::tag_metadata(doc.metadata.clone(), &doc.content),
+                )),
+                Err(error) => {
+                    tracing::warn!("Reindex failed for {}: {}", doc.id, error);
+                    summary.failed += 1;
+                    summary.failures.push(RagReindexFailure { id: doc.id.clone(), error });
+                }
+            }
+        }
+
+        if !new_docs.is_empty() {
+            let mut store = load_vector_store(&store_path)?;
+            for embedding in new_docs.iter().filter_map(|d| d["embedding"].as_array()) {
+                ensure_store_dimension(&mut store, embedding.len())?;
+            }
+            done.extend(new_docs.iter().filter_map(|d| d["id"].as_str().map(str::to_string)));
+            summary.succeeded += new_docs.len() as u32;
+            upsert_documents(&mut store, new_docs);
+
+            let content = serde_json::to_string(&store).map_err(|e| e.to_string())?;
+            fs::write(&store_path, content).map_err(|e| e.to_string())?;
+            let checkpoint = serde_json::to_string(&done).map_err(|e| e.to_string())?;
+            fs::write(&checkpoint_path, checkpoint).map_err(|e| e.to_string())?;
+        }
+
+        let _ = window.emit(
+            "rag-reindex-progress",
+            &RagReindexProgress {
+                collection: collection_name.clone(),
+                processed: summary.skipped + summary.succeeded + summary.failed,
+                total,
+                succeeded: summary.succeeded,
+                failed: summary.failed,
+            },
+        );
+    }
+
+    REINDEX_CANCELLED.write().remove(&collection_name);
+
+    if !summary.cancelled && summary.failed == 0 && checkpoint_path.exists() {
+        let _ = fs::remove_file(&checkpoint_path);
+    }
+
+    Ok(summary)
+}
+
+/// Re-embed every document in `collection` with the current embedding
+/// provider, e.g. after switching models. The store is only replaced if every
+/// document embeds successfully, so a failed run leaves it usable.
+#[tauri::command]
+pub async fn learning_rag_reembed(window: Window, collection: Option<String>) -> Result<RagReindexSummary, String> {
+    use futures_util::future::join_all;
+
+    let collection_name = collection.unwrap_or_else(|| "default".to_string());
+    let store_path = vector_store_path(Some(&collection_name))?;
+    let store = load_vector_store(&store_path)?;
+    let documents = store["documents"].as_array().cloned().unwrap_or_default();
+
+    let total = documents.len() as u32;
+    let mut summary = RagReindexSummary::default();
+    let mut rebuilt = serde_json::json!({ "version": 1, "documents": [] });
+    let mut rebuilt_docs = Vec::with_capacity(documents.len());
+
+    for batch in documents.chunks(REINDEX_BATCH_SIZE) {
+        let embeddings = join_all(
+            batch
+                .iter()
+                .map(|doc| get_embedding(doc["content"].as_str().unwrap_or_default())),
+        )
+        .await;
+
+        for (doc, embedding) in batch.iter().zip(embeddings) {
+            let id = doc["id"].as_str().unwrap_or_default().to_string();
+            match embedding.and_then(|e| ensure_store_dimension(&mut rebuilt, e.len()).map(|_| e)) {
+                Ok(embedding) => {
+                    let mut new_doc = rag_document_json(
+                        &id,
+                        doc["content"].as_str().unwrap_or_default(),
+                        embedding,
+                        doc["source"].as_str().unwrap_or(UNKNOWN_SOURCE),
+                        doc["source_type"].as_str(),
+                        doc.get("metadata").cloned(),
+                    );
+                    if let Some(created_at) = doc.get("created_at") {
+                        new_doc["created_at"] = created_at.clone();
+                    }
+                    rebuilt_docs.push(new_doc);
+                    summary.succeeded += 1;
+                }
+                Err(error) => {
+                    summary.failed += 1;
+                    summary.failures.push(RagReindexFailure { id, error });
+                }
+            }
+        }
+
+        let _ = window.emit(
+            "rag-reindex-progress",
+            &RagReindexProgress {
+                collection: collection_name.clone(),
+                processed: summary.succeeded + summary.failed,
+                total,
+                succeeded: summary.succeeded,
+                failed: summary.failed,
+            },
+        );
+    }
+
+    if summary.failed == 0 && total > 0 {
+        rebuilt["documents"] = serde_json::Value::Array(rebuilt_docs);
+        let content = serde_json::to_string(&rebuilt).map_err(|e| e.to_string())?;
+        fs::write(&store_path, content).map_err(|e| e.to_string())?;
+    }
+
+    Ok(summary)
+}
+
+/// Stop a running reindex of `collection` after its current batch
+#[tauri::command]
+pub fn learning_rag_cancel_reindex(collection: Option<String>) -> Result<(), String> {
+    REINDEX_CANCELLED
+        .write()
+        .insert(collection.unwrap_or_else(|| "default".to_string()));
+    Ok(())
+}
+
 #[tauri::command]
 pub fn learning_rag_clear() -> Result<(), String> {
     let vectors_path = get_vectors_dir().join("default.json");
@@ -416,6 +1195,16 @@ pub fn learning_collect_training(
 #[tauri::command]
 pub fn learning_get_training_examples(limit: Option<u32>) -> Result<Vec<TrainingExample>, String> {
     let limit = limit.unwrap_or(50) as usize;
+    let mut examples = read_instruction_examples();
+
+    // Sort by date descending
+    examples.sort_by(|a, b| b.collected_at.cmp(&a.collected_at));
+    examples.truncate(limit);
+
+    Ok(examples)
+}
+
+fn read_instruction_examples() -> Vec<TrainingExample> {
     let training_dir = get_training_dir();
     let mut examples: Vec<TrainingExample> = vec![];
 
@@ -444,15 +1233,208 @@ pub fn learning_get_training_examples(limit: Option<u32>) -> Result<Vec<Training
         }
     }
 
-    // Sort by date descending
-    examples.sort_by(|a, b| b.collected_at.cmp(&a.collected_at));
-    examples.truncate(limit);
+    examples
+}
 
-    Ok(examples)
+/// Check every `.jsonl` file in the training directory before export
+#[tauri::command]
+pub fn learning_validate_training_data() -> Result<ValidationReport, String> {
+    let mut report = ValidationReport::default();
+
+    let mut files: Vec<PathBuf> = fs::read_dir(get_training_dir())
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().map(|e| e == "jsonl").unwrap_or(false))
+        .collect();
+    files.sort();
+
+    for path in files {
+        let bytes = fs::read(&path).map_err(|e| e.to_string())?;
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        validate_training_file(&name, &bytes, &mut report);
+    }
+
+    Ok(report)
 }
 
+/// The user turn of a two-turn conversation: instruction plus optional input
+fn user_turn(example: &TrainingExample) -> String {
+    if example.input.trim().is_empty() {
+        example.instruction.clone()
+    } else {
+        format!("{}\n\n{}", example.instruction, example.input)
+    }
+}
+
+fn to_export_record(example: &TrainingExample, format: ExportFormat) -> serde_json::Value {
+    match format {
+        ExportFormat::Alpaca => serde_json::json!({
+            "instruction": example.instruction,
+            "input": example.input,
+            "output": example.output,
+        }),
+        ExportFormat::ShareGPT => serde_json::json!({
+            "conversations": [
+                { "from": "human", "value": user_turn(example) },
+                { "from": "gpt", "value": example.output },
+            ]
+        }),
+        ExportFormat::OpenAI => serde_json::json!({
+            "messages": [
+                { "role": "user", "content": user_turn(example) },
+                { "role": "assistant", "content": example.output },
+            ]
+        }),
+    }
+}
+
+/// Every 10th example goes to the eval split
+const EVAL_EVERY: usize = 10;
+
+/// Export in a conversation format straight from the instruction files
+fn export_conversations(format: ExportFormat, name: &str) -> Result<ExportResult, String> {
+    let mut examples = read_instruction_examples();
+    examples.sort_by(|a, b| a.collected_at.cmp(&b.collected_at));
+    write_export(&examples, format, name)
+}
+
+/// Write `examples` as train/eval JSONL files named after `name`
+fn write_export(examples: &[TrainingExample], format: ExportFormat, name: &str) -> Result<ExportResult, String> {
+    let export_dir = get_data_dir().join("export");
+    fs::create_dir_all(&export_dir).map_err(|e| e.to_string())?;
+
+    let (mut train, mut eval) = (String::new(), String::new());
+    let (mut train_count, mut eval_count) = (0u32, 0u32);
+    for (index, example) in examples.iter().enumerate() {
+        let line = serde_json::to_string(&to_export_record(example, format)).map_err(|e| e.to_string())?;
+        if (index + 1) % EVAL_EVERY == 0 {
+            eval.push_str(&line);
+            eval.push('\n');
+            eval_count += 1;
+        } else {
+            train.push_str(&line);
+            train.push('\n');
+            train_count += 1;
+        }
+    }
+
+    let train_path = export_dir.join(format!("train-{}.jsonl", name));
+    let eval_path = export_dir.join(format!("eval-{}.jsonl", name));
+    fs::write(&train_path, train).map_err(|e| e.to_string())?;
+    fs::write(&eval_path, eval).map_err(|e| e.to_string())?;
+
+    Ok(ExportResult {
+        format,
+        train_path: train_path.to_string_lossy().to_string(),
+        eval_path: eval_path.to_string_lossy().to_string(),
+        train_count,
+        eval_count,
+        notebook_path: None,
+    })
+}
+
+#[tauri::command]
+pub fn learning_export_for_finetune(format: Option<ExportFormat>) -> Result<ExportResult, String> {
+    match format.unwrap_or_default() {
+        ExportFormat::Alpaca => export_alpaca(),
+        ExportFormat::ShareGPT => export_conversations(ExportFormat::ShareGPT, "sharegpt"),
+        ExportFormat::OpenAI => export_conversations(ExportFormat::OpenAI, "openai"),
+    }
+}
+
+/// Default cap on examples kept from a single collection day
+const DEFAULT_MAX_PER_DAY: u32 = 200;
+/// Default cap on examples in a balanced export
+const DEFAULT_MAX_TOTAL: u32 = 2000;
+
+/// `YYYY-MM-DD` part of `collected_at`, or `"unknown"`
+fn day_bucket(example: &TrainingExample) -> &str {
+    example
+        .collected_at
+        .get(..10)
+        .filter(|date| date.as_bytes()[4] == b'-' && date.as_bytes()[7] == b'-')
+        .unwrap_or("unknown")
+}
+
+/// Fisher-Yates with a xorshift generator; good enough for sampling and
+/// avoids pulling in `rand` for one call site
+fn shuffle<T>(items: &mut [T], seed: u64) {
+    let mut state = seed | 1;
+    for i in (1..items.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        items.swap(i, (state % (i as u64 + 1)) as usize);
+    }
+}
+
+/// Keep a random sample of at most `max_per_day` examples per day, then a
+/// random sample of at most `max_total` overall, in shuffled order
+fn balance_examples(
+    examples: Vec<TrainingExample>,
+    max_per_day: usize,
+    max_total: usize,
+    seed: u64,
+) -> Vec<TrainingExample> {
+    let mut by_day: BTreeMap<String, Vec<TrainingExample>> = BTreeMap::new();
+    for example in examples {
+        by_day.entry(day_bucket(&example).to_string()).or_default().push(example);
+    }
+
+    let mut balanced = Vec::new();
+    for (offset, mut bucket) in by_day.into_values().enumerate() {
+        shuffle(&mut bucket, seed.wrapping_add(offset as u64));
+        bucket.truncate(max_per_day);
+        balanced.extend(bucket);
+    }
+
+    shuffle(&mut balanced, seed.rotate_left(32));
+    balanced.truncate(max_total);
+    balanced
+}
+
+/// Export with each day's examples capped so busy days don't dominate the dataset
+#[tauri::command]
+pub fn learning_export_balanced(
+    format: Option<ExportFormat>,
+    max_per_day: Option<u32>,
+    max_total: Option<u32>,
+) -> Result<ExportResult, String> {
+    let format = format.unwrap_or_default();
+    let seed = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0x9E37_79B9_7F4A_7C15);
+
+    let examples = balance_examples(
+        read_instruction_examples(),
+        max_per_day.unwrap_or(DEFAULT_MAX_PER_DAY) as usize,
+        max_total.unwrap_or(DEFAULT_MAX_TOTAL) as usize,
+        seed,
+    );
+
+    let name = match format {
+        ExportFormat::Alpaca => "balanced-alpaca",
+        ExportFormat::ShareGPT => "balanced-sharegpt",
+        ExportFormat::OpenAI => "balanced-openai",
+    };
+    write_export(&examples, format, name)
+}
+
+/// Instruction examples per collection day, oldest first
 #[tauri::command]
-pub fn learning_export_for_finetune() -> Result<ExportResult, String> {
+pub fn learning_get_training_distribution() -> Result<Vec<DayBucket>, String> {
+    let mut counts: BTreeMap<String, u32> = BTreeMap::new();
+    for example in read_instruction_examples() {
+        *counts.entry(day_bucket(&example).to_string()).or_default() += 1;
+    }
+
+    Ok(counts.into_iter().map(|(date, count)| DayBucket { date, count }).collect())
+}
+
+/// Alpaca export via the Node learning CLI, which also writes the notebook
+fn export_alpaca() -> Result<ExportResult, String> {
     let learning_dir = get_learning_dir();
 
     // Run the export script via Node.js
@@ -471,23 +1453,26 @@ pub fn learning_export_for_finetune() -> Result<ExportResult, String> {
     let export_dir = get_data_dir().join("export");
 
     Ok(ExportResult {
+        format: ExportFormat::Alpaca,
         train_path: export_dir.join("train-alpaca.jsonl").to_string_lossy().to_string(),
         eval_path: export_dir.join("eval-alpaca.jsonl").to_string_lossy().to_string(),
         train_count: 0, // Would need to parse output
         eval_count: 0,
-        notebook_path: export_dir.join("fine-tune-ollama.ipynb").to_string_lossy().to_string(),
+        notebook_path: Some(export_dir.join("fine-tune-ollama.ipynb").to_string_lossy().to_string()),
     })
 }
 
 #[tauri::command]
 pub async fn learning_pull_embedding_model() -> Result<String, String> {
-    let client = reqwest::Client::new();
-    let ollama_url = std::env::var("OLLAMA_URL").unwrap_or_else(|_| "http://localhost:11434".to_string());
+    let EmbeddingProvider::Ollama { url, model, .. } = EMBEDDING_PROVIDER.read().clone() else {
+        return Err("The current embedding provider is not Ollama".to_string());
+    };
 
+    let client = reqwest::Client::new();
     let response = client
-        .post(format!("{}/api/pull", ollama_url))
+        .post(format!("{}/api/pull", url.trim_end_matches('/')))
         .json(&serde_json::json!({
-            "name": "mxbai-embed-large",
+            "name": model,
             "stream": false
         }))
         .timeout(std::time::Duration::from_secs(600))
@@ -496,12 +1481,50 @@ pub async fn learning_pull_embedding_model() -> Result<String, String> {
         .map_err(|e| format!("Pull request failed: {}", e))?;
 
     if response.status().is_success() {
-        Ok("mxbai-embed-large installed successfully".to_string())
+        Ok(format!("{} installed successfully", model))
     } else {
         Err(format!("Pull failed: {}", response.status()))
     }
 }
 
+/// Switch the embedding service used for RAG. Documents embedded by another
+/// model are not comparable, so stores may need rebuilding afterwards.
+#[tauri::command]
+pub fn set_embedding_provider(provider: EmbeddingProvider) -> Result<(), String> {
+    let (base, model) = match &provider {
+        EmbeddingProvider::Ollama { url, model, .. } => (url, model),
+        EmbeddingProvider::OpenAI { api_base, model, .. } => (api_base, model),
+    };
+    reqwest::Url::parse(base).map_err(|e| format!("Invalid URL '{}': {}", base, e))?;
+    if model.trim().is_empty() {
+        return Err("Embedding model cannot be empty".to_string());
+    }
+    if provider.max_input_tokens() == 0 {
+        return Err("max_input_tokens must be greater than 0".to_string());
+    }
+
+    let mut provider = provider;
+    // The UI never sees the stored key, so an empty one means "keep it"
+    if let EmbeddingProvider::OpenAI { api_key, .. } = &mut provider {
+        if api_key.is_empty() {
+            if let EmbeddingProvider::OpenAI { api_key: current, .. } = &*EMBEDDING_PROVIDER.read() {
+                api_key.clone_from(current);
+            }
+        }
+    }
+
+    save_embedding_provider(&provider)?;
+    *EMBEDDING_PROVIDER.write() = provider;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_current_embedding_provider() -> Result<EmbeddingProviderView, String> {
+    let provider = EMBEDDING_PROVIDER.read().clone();
+    let has_api_key = matches!(&provider, EmbeddingProvider::OpenAI { api_key, .. } if !api_key.is_empty());
+    Ok(EmbeddingProviderView { provider, has_api_key })
+}
+
 // ============================================================================
 // Alzur - AI Trainer Commands
 // ============================================================================
@@ -519,10 +1542,82 @@ pub struct TrainingConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrainingResult {
     pub success: bool,
+    pub job_id: String,
     pub model_path: Option<String>,
     pub error: Option<String>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrainingState {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrainingJobStatus {
+    pub job_id: String,
+    pub output_model: String,
+    pub base_model: String,
+    pub started_at: String,
+    pub status: TrainingState,
+    pub progress: Option<f32>,
+}
+
+/// Payload of the `training-progress` event. Every event but the last has
+/// state `running`; the last one carries the final state.
+#[derive(Debug, Clone, Serialize)]
+pub struct TrainingProgressEvent {
+    pub job_id: String,
+    pub state: TrainingState,
+    /// Status line reported by Ollama, absent on the final event
+    pub status: Option<String>,
+    /// Set when the state is `failed`
+    pub error: Option<String>,
+    pub percentage: Option<f32>,
+    pub layer: Option<u32>,
+}
+
+/// One status line of the streamed `/api/create` response
+#[derive(Debug, Deserialize)]
+struct CreateStatusLine {
+    #[serde(default)]
+    status: String,
+    total: Option<u64>,
+    completed: Option<u64>,
+}
+
+lazy_static::lazy_static! {
+    static ref TRAINING_JOBS: RwLock<HashMap<String, TrainingJobStatus>> = RwLock::new(HashMap::new());
+    /// Cancel signal of each running job, so a stalled `/api/create` request
+    /// is abandoned without waiting for its next status line
+    static ref TRAINING_CANCEL: RwLock<HashMap<String, Arc<tokio::sync::Notify>>> = RwLock::new(HashMap::new());
+}
+
+fn set_job_status(job_id: &str, status: TrainingState, progress: Option<f32>) {
+    if let Some(job) = TRAINING_JOBS.write().get_mut(job_id) {
+        // A cancelled job stays cancelled even if Ollama finishes afterwards
+        if job.status != TrainingState::Cancelled {
+            job.status = status;
+            job.progress = progress;
+        }
+    }
+}
+
+fn is_job_cancelled(job_id: &str) -> bool {
+    TRAINING_JOBS
+        .read()
+        .get(job_id)
+        .map(|job| job.status == TrainingState::Cancelled)
+        .unwrap_or(false)
+}
+
+fn emit_training_progress(window: &Window, event: TrainingProgressEvent) {
+    let _ = window.emit("training-progress", &event);
+}
+
 /// Write training dataset to JSONL file (for Alzur)
 #[tauri::command]
 pub fn write_training_dataset(filename: String, content: String) -> Result<String, String> {
@@ -535,10 +1630,29 @@ pub fn write_training_dataset(filename: String, content: String) -> Result<Strin
 }
 
 /// Start model fine-tuning via Ollama (for Alzur)
+///
+/// Returns as soon as the job is registered; progress is reported through
+/// `training-progress` events and the job registry.
 #[tauri::command]
-pub async fn start_model_training(config: TrainingConfig) -> Result<TrainingResult, String> {
+pub async fn start_model_training(window: Window, config: TrainingConfig) -> Result<TrainingResult, String> {
     let ollama_url = std::env::var("OLLAMA_URL").unwrap_or_else(|_| "http://localhost:11434".to_string());
 
+    // Register the job before anything can fail so it is always visible
+    let job_id = uuid::Uuid::new_v4().to_string();
+    TRAINING_JOBS.write().insert(
+        job_id.clone(),
+        TrainingJobStatus {
+            job_id: job_id.clone(),
+            output_model: config.output_model.clone(),
+            base_model: config.base_model.clone(),
+            started_at: chrono::Utc::now().to_rfc3339(),
+            status: TrainingState::Running,
+            progress: Some(0.0),
+        },
+    );
+    let cancel = Arc::new(tokio::sync::Notify::new());
+    TRAINING_CANCEL.write().insert(job_id.clone(), cancel.clone());
+
     // Step 1: Create Modelfile for fine-tuning
     let training_dir = get_training_dir();
     let modelfile_path = training_dir.join(format!("{}.Modelfile", config.output_model));
@@ -567,10 +1681,78 @@ Dataset: {}
         config.dataset_path
     );
 
-    fs::write(&modelfile_path, &modelfile_content)
-        .map_err(|e| format!("Failed to create Modelfile: {}", e))?;
+    if let Err(e) = fs::write(&modelfile_path, &modelfile_content) {
+        set_job_status(&job_id, TrainingState::Failed, None);
+        TRAINING_CANCEL.write().remove(&job_id);
+        return Err(format!("Failed to create Modelfile: {}", e));
+    }
 
-    // Step 2: Create model via Ollama API
+    // Step 2: Create model via Ollama API in the background
+    let task_job_id = job_id.clone();
+    let output_model = config.output_model.clone();
+    tokio::spawn(async move {
+        // Dropping the request closes the connection, which stops Ollama
+        let result = tokio::select! {
+            result = run_training_job(&window, &task_job_id, &ollama_url, &config, &modelfile_content) => result,
+            _ = cancel.notified() => Ok(()),
+        };
+        TRAINING_CANCEL.write().remove(&task_job_id);
+
+        let (state, error) = match result {
+            _ if is_job_cancelled(&task_job_id) => (TrainingState::Cancelled, None),
+            Ok(()) => {
+                set_job_status(&task_job_id, TrainingState::Completed, Some(100.0));
+
+                // Save training log
+                let log_path = training_dir.join(format!("{}.log", config.output_model));
+                let log_content = format!(
+                    "Training completed at: {}\nBase model: {}\nOutput model: {}\nDataset: {}\nEpochs: {}\n",
+                    chrono::Utc::now().to_rfc3339(),
+                    config.base_model,
+                    config.output_model,
+                    config.dataset_path,
+                    config.epochs
+                );
+                let _ = fs::write(&log_path, log_content);
+                (TrainingState::Completed, None)
+            }
+            Err(e) => {
+                tracing::error!("Training job {} failed: {}", task_job_id, e);
+                set_job_status(&task_job_id, TrainingState::Failed, None);
+                (TrainingState::Failed, Some(e))
+            }
+        };
+
+        let percentage = TRAINING_JOBS.read().get(&task_job_id).and_then(|job| job.progress);
+        emit_training_progress(
+            &window,
+            TrainingProgressEvent {
+                job_id: task_job_id,
+                state,
+                status: None,
+                error,
+                percentage,
+                layer: None,
+            },
+        );
+    });
+
+    Ok(TrainingResult {
+        success: true,
+        job_id,
+        model_path: Some(output_model),
+        error: None,
+    })
+}
+
+/// Stream `/api/create` and forward each status line as a progress event
+async fn run_training_job(
+    window: &Window,
+    job_id: &str,
+    ollama_url: &str,
+    config: &TrainingConfig,
+    modelfile_content: &str,
+) -> Result<(), String> {
     let client = reqwest::Client::new();
 
     let response = client
@@ -578,46 +1760,76 @@ Dataset: {}
         .json(&serde_json::json!({
             "name": config.output_model,
             "modelfile": modelfile_content,
-            "stream": false
+            "stream": true
         }))
         .timeout(std::time::Duration::from_secs(3600)) // 1 hour timeout for training
         .send()
         .await
         .map_err(|e| format!("Training request failed: {}", e))?;
 
-    if response.status().is_success() {
-        // Save training log
-        let log_path = training_dir.join(format!("{}.log", config.output_model));
-        let log_content = format!(
-            "Training completed at: {}\nBase model: {}\nOutput model: {}\nDataset: {}\nEpochs: {}\n",
-            chrono::Utc::now().to_rfc3339(),
-            config.base_model,
-            config.output_model,
-            config.dataset_path,
-            config.epochs
-        );
-        let _ = fs::write(&log_path, log_content);
-
-        Ok(TrainingResult {
-            success: true,
-            model_path: Some(config.output_model),
-            error: None,
-        })
-    } else {
+    if !response.status().is_success() {
+        let status = response.status();
         let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        Ok(TrainingResult {
-            success: false,
-            model_path: None,
-            error: Some(error_text),
-        })
+        return Err(format!("{}: {}", status, error_text));
+    }
+
+    let mut lines = NdjsonReader::new(response.bytes_stream());
+    let mut layer = 0u32;
+
+    while let Some(line) = lines.next_line().await {
+        let line = line.map_err(|e| format!("Training stream failed: {}", e))?;
+
+        let update: CreateStatusLine = match parse_line(&line) {
+            Ok(update) => update,
+            Err(AppError::Stream(error)) => return Err(error),
+            Err(e) => {
+                tracing::warn!("Failed to parse training status: {} - {}", line, e);
+                continue;
+            }
+        };
+
+        if update.status.contains("layer") {
+            layer += 1;
+        }
+
+        let percentage = match (update.completed, update.total) {
+            (Some(completed), Some(total)) if total > 0 => Some(completed as f32 / total as f32 * 100.0),
+            _ => None,
+        };
+
+        if percentage.is_some() {
+            set_job_status(job_id, TrainingState::Running, percentage);
+        }
+        emit_training_progress(
+            window,
+            TrainingProgressEvent {
+                job_id: job_id.to_string(),
+                state: TrainingState::Running,
+                status: Some(update.status),
+                error: None,
+                percentage,
+                layer: (layer > 0).then_some(layer),
+            },
+        );
     }
+
+    Ok(())
 }
 
 /// Cancel ongoing model training
 #[tauri::command]
 pub fn cancel_model_training(job_id: String) -> Result<bool, String> {
-    // For now, we just log the cancellation
-    // Full implementation would require tracking running jobs
+    // Ollama has no cancel endpoint for /api/create; the signal makes the
+    // training task drop the connection
+    if let Some(job) = TRAINING_JOBS.write().get_mut(&job_id) {
+        if job.status == TrainingState::Running {
+            job.status = TrainingState::Cancelled;
+        }
+    }
+    if let Some(cancel) = TRAINING_CANCEL.read().get(&job_id) {
+        cancel.notify_one();
+    }
+
     let training_dir = get_training_dir();
     let cancel_log = training_dir.join(format!("{}.cancelled", job_id));
 
@@ -646,3 +1858,258 @@ pub fn get_alzur_models() -> Result<Vec<String>, String> {
 
     Ok(models)
 }
+
+/// List all training jobs started in this session, newest first
+#[tauri::command]
+pub fn get_training_jobs() -> Result<Vec<TrainingJobStatus>, String> {
+    let mut jobs: Vec<TrainingJobStatus> = TRAINING_JOBS.read().values().cloned().collect();
+    jobs.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+    Ok(jobs)
+}
+
+#[tauri::command]
+pub fn get_training_job(job_id: String) -> Result<Option<TrainingJobStatus>, String> {
+    Ok(TRAINING_JOBS.read().get(&job_id).cloned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naive_cosine(a: &[f64], b: &[f64]) -> f64 {
+        let dot: f64 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+        let norm_a = a.iter().map(|x| x * x).sum::<f64>().sqrt();
+        let norm_b = b.iter().map(|x| x * x).sum::<f64>().sqrt();
+        dot / (norm_a * norm_b)
+    }
+
+    fn pseudo_random_vector(seed: u64, dim: usize) -> Vec<f64> {
+        let mut state = seed;
+        (0..dim)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (state >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_upsert_documents_replaces_by_id() {
+        let mut store = serde_json::json!({ "version": 1, "documents": [
+            { "id": "a", "content": "old" },
+            { "id": "b", "content": "keep" }
+        ]});
+
+        upsert_documents(
+            &mut store,
+            vec![serde_json::json!({ "id": "a", "content": "new" }), serde_json::json!({ "id": "c", "content": "added" })],
+        );
+
+        let docs = store["documents"].as_array().unwrap();
+        assert_eq!(docs.len(), 3);
+        let content_of = |id: &str| docs.iter().find(|d| d["id"] == id).unwrap()["content"].clone();
+        assert_eq!(content_of("a"), "new");
+        assert_eq!(content_of("b"), "keep");
+        assert_eq!(content_of("c"), "added");
+    }
+
+    #[test]
+    fn test_balance_examples_caps_each_day() {
+        let example = |day: &str, n: usize| TrainingExample {
+            instruction: format!("task {}", n),
+            input: String::new(),
+            output: String::new(),
+            collected_at: format!("{}T12:00:00Z", day),
+        };
+        let mut examples: Vec<TrainingExample> = (0..50).map(|n| example("2025-03-01", n)).collect();
+        examples.extend((0..3).map(|n| example("2025-03-02", n)));
+        examples.push(TrainingExample { collected_at: String::new(), ..example("", 99) });
+
+        let balanced = balance_examples(examples.clone(), 5, 100, 42);
+        let count = |day: &str| balanced.iter().filter(|e| day_bucket(e) == day).count();
+        assert_eq!(count("2025-03-01"), 5);
+        assert_eq!(count("2025-03-02"), 3);
+        assert_eq!(count("unknown"), 1);
+
+        assert_eq!(balance_examples(examples, 5, 4, 42).len(), 4);
+    }
+
+    #[test]
+    fn test_store_dimension_mismatch() {
+        let mut legacy = serde_json::json!({ "version": 1, "documents": [{ "id": "a", "embedding": [0.1, 0.2, 0.3] }] });
+        assert_eq!(store_dimension(&legacy), Some(3));
+        assert!(ensure_store_dimension(&mut legacy, 4).is_err());
+        assert!(ensure_store_dimension(&mut legacy, 3).is_ok());
+        assert_eq!(legacy["dimension"], 3);
+
+        let mut empty = serde_json::json!({ "version": 1, "documents": [] });
+        assert_eq!(store_dimension(&empty), None);
+        assert!(ensure_store_dimension(&mut empty, 1024).is_ok());
+        assert!(ensure_store_dimension(&mut empty, 768).unwrap_err().contains("1024"));
+    }
+
+    #[test]
+    fn test_validate_training_file() {
+        let data = [
+            r#"{"instruction":"Explain borrowing","output":"References without ownership"}"#.as_bytes(),
+            b"",
+            r#"{"instruction":"Hi","output":"short"}"#.as_bytes(),
+            r#"{"output":"Long enough output here"}"#.as_bytes(),
+            b"not json",
+            b"{\"instruction\":\"x\",\"output\":\"\xff\xfe invalid utf8\"}",
+        ]
+        .join(&b'\n');
+
+        let mut report = ValidationReport::default();
+        validate_training_file("a.jsonl", &data, &mut report);
+
+        assert_eq!((report.total_lines, report.valid, report.invalid), (5, 1, 4));
+        let fields: Vec<(u32, &str)> = report.errors.iter().map(|e| (e.line, e.field.as_str())).collect();
+        assert_eq!(fields, vec![(3, "output"), (4, "instruction"), (5, ""), (6, "")]);
+    }
+
+    #[test]
+    fn test_export_record_formats() {
+        let example = TrainingExample {
+            instruction: "Translate".to_string(),
+            input: "kot".to_string(),
+            output: "cat".to_string(),
+            collected_at: String::new(),
+        };
+
+        let sharegpt = to_export_record(&example, ExportFormat::ShareGPT);
+        assert_eq!(sharegpt["conversations"][0]["from"], "human");
+        assert_eq!(sharegpt["conversations"][0]["value"], "Translate\n\nkot");
+        assert_eq!(sharegpt["conversations"][1]["value"], "cat");
+
+        let openai = to_export_record(&example, ExportFormat::OpenAI);
+        assert_eq!(openai["messages"][1]["role"], "assistant");
+        assert_eq!(openai["messages"][1]["content"], "cat");
+
+        assert_eq!(serde_json::to_string(&ExportFormat::ShareGPT).unwrap(), "\"sharegpt\"");
+    }
+
+    #[test]
+    fn test_cosine_matches_naive() {
+        // 1027 exercises both the 8-wide lanes and the remainder
+        let a = pseudo_random_vector(1, 1027);
+        let b = pseudo_random_vector(2, 1027);
+        assert!((cosine_similarity(&a, &b) - naive_cosine(&a, &b)).abs() < 1e-12);
+        assert!((cosine_similarity(&a, &a) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_cosine_guards() {
+        assert_eq!(cosine_similarity(&[], &[]), 0.0);
+        assert_eq!(cosine_similarity(&[1.0, 2.0], &[1.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 1.0]), 0.0);
+    }
+
+    #[test]
+    fn test_parse_embedding_response_shapes() {
+        let fixtures = [
+            // Ollama /api/embed, batch of one
+            serde_json::json!({ "model": "m", "embeddings": [[0.1, 0.2, 0.3]] }),
+            // Ollama /api/embed, flat vector
+            serde_json::json!({ "model": "m", "embeddings": [0.1, 0.2, 0.3] }),
+            // Ollama /api/embeddings (legacy)
+            serde_json::json!({ "embedding": [0.1, 0.2, 0.3] }),
+            // OpenAI
+            serde_json::json!({ "object": "list", "data": [{ "index": 0, "embedding": [0.1, 0.2, 0.3] }] }),
+        ];
+        for fixture in &fixtures {
+            assert_eq!(parse_embedding_response(fixture).unwrap(), vec![0.1, 0.2, 0.3], "{}", fixture);
+        }
+
+        let batch = serde_json::json!({ "embeddings": [[1.0, 2.0], [3.0, 4.0]] });
+        assert_eq!(parse_embeddings_response(&batch).unwrap(), vec![vec![1.0, 2.0], vec![3.0, 4.0]]);
+    }
+
+    #[test]
+    fn test_parse_embedding_response_rejects_bad_vectors() {
+        let fixtures = [
+            serde_json::json!({ "embeddings": [] }),
+            serde_json::json!({ "embeddings": [[]] }),
+            serde_json::json!({ "embeddings": [[1.0], []] }),
+            serde_json::json!({ "embedding": [] }),
+            serde_json::json!({ "embedding": [1.0, "x"] }),
+            serde_json::json!({ "data": [] }),
+            serde_json::json!({ "error": "model not found" }),
+        ];
+        for fixture in &fixtures {
+            assert!(parse_embedding_response(fixture).is_err(), "{}", fixture);
+        }
+
+        let err = parse_embedding_response(&serde_json::json!({ "error": "model not found" })).unwrap_err();
+        assert!(err.contains("model not found"));
+    }
+
+    #[test]
+    fn test_split_for_embedding() {
+        assert!(split_for_embedding("   ", 10).is_empty());
+        assert_eq!(split_for_embedding("short text", 100), ["short text"]);
+        assert_eq!(split_for_embedding("alpha beta gamma delta", 12), ["alpha beta", "gamma delta"]);
+        // No whitespace to break at: hard split on characters, not bytes
+        assert_eq!(split_for_embedding("ąęółźćżń", 3), ["ąęó", "łźć", "żń"]);
+
+        let long = "word ".repeat(1000);
+        let chunks = split_for_embedding(&long, 64);
+        assert!(chunks.iter().all(|c| c.chars().count() <= 64 && !c.is_empty()));
+        assert_eq!(chunks.iter().map(|c| c.split_whitespace().count()).sum::<usize>(), 1000);
+    }
+
+    #[test]
+    fn test_mean_pool_weights_by_length() {
+        let pooled = mean_pool(&[vec![1.0, 0.0], vec![0.0, 1.0]], &[3, 1]).unwrap();
+        assert_eq!(pooled, vec![0.75, 0.25]);
+        assert_eq!(mean_pool(&[vec![2.0, 4.0]], &[7]).unwrap(), vec![2.0, 4.0]);
+        assert!(mean_pool(&[vec![1.0], vec![1.0, 2.0]], &[1, 1]).is_err());
+        assert!(mean_pool(&[], &[]).is_err());
+    }
+
+    #[test]
+    fn test_provider_json_omits_api_key() {
+        let provider = EmbeddingProvider::OpenAI {
+            api_base: "https://api.openai.com".to_string(),
+            api_key: "sk-secret".to_string(),
+            model: "text-embedding-3-small".to_string(),
+            max_input_tokens: 8191,
+        };
+        let json = serde_json::to_string(&EmbeddingProviderView {
+            provider: provider.clone(),
+            has_api_key: true,
+        })
+        .unwrap();
+        assert!(!json.contains("sk-secret"));
+        assert!(json.contains("\"has_api_key\":true"));
+
+        // Keys saved by older versions still load, so they can be migrated
+        let old = r#"{"type":"openai","api_base":"https://api.openai.com","api_key":"sk-secret","model":"text-embedding-3-small","max_input_tokens":8191}"#;
+        assert_eq!(serde_json::from_str::<EmbeddingProvider>(old).unwrap(), provider);
+    }
+
+    /// Timing comparison; run with `cargo test --release -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_cosine_vs_naive() {
+        let query = pseudo_random_vector(0, 1024);
+        let docs: Vec<Vec<f64>> = (1..=5000).map(|i| pseudo_random_vector(i, 1024)).collect();
+        let norms: Vec<f64> = docs.iter().map(|d| vector_norm(d)).collect();
+
+        let start = std::time::Instant::now();
+        let naive: f64 = docs.iter().map(|d| naive_cosine(&query, d)).sum();
+        let naive_time = start.elapsed();
+
+        let query_norm = vector_norm(&query);
+        let start = std::time::Instant::now();
+        let fast: f64 = docs
+            .iter()
+            .zip(&norms)
+            .map(|(d, n)| cosine_similarity_with_norms(&query, query_norm, d, *n))
+            .sum();
+        let fast_time = start.elapsed();
+
+        println!("naive: {:?}, precomputed norms + lanes: {:?}", naive_time, fast_time);
+        assert!((naive - fast).abs() < 1e-6);
+    }
+}
@@ -1,25 +1,33 @@
 mod agentic;
 mod bridge;
+mod bridge_server;
 mod chat_history;
 mod claude;
+mod code_language;
 mod commands;
 mod debug;
+mod deep_link;
+mod error;
+mod health;
 mod learning;
+mod logging;
+mod math;
 mod memory;
 mod ollama;
 mod ollama_commands;
 mod parallel;
+mod response_cache;
+mod security;
+mod tools;
+mod updates;
+mod utils;
 
 use tauri::Manager;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    // Initialize logging
-    let _ = tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer())
-        .with(tracing_subscriber::EnvFilter::from_default_env())
-        .try_init();
+    // Initialize logging (stderr + rotated log file)
+    logging::init();
 
     // DevTools - only in debug builds for performance/security
     #[cfg(debug_assertions)]
@@ -27,6 +35,19 @@ pub fn run() {
 
     let mut builder = tauri::Builder::default();
 
+    // Must be registered before any other plugin. A second launch (e.g. a
+    // geminicli:// link clicked while the app runs) exits and hands its
+    // arguments over; the `deep-link` feature routes the URL to `on_open_url`
+    #[cfg(any(windows, target_os = "linux"))]
+    {
+        builder = builder.plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }));
+    }
+
     #[cfg(debug_assertions)]
     {
         builder = builder.plugin(devtools);
@@ -37,6 +58,7 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_deep_link::init())
         .setup(|app| {
             // Initialize Claude state
             let claude_state = claude::state::AppState::new();
@@ -49,6 +71,9 @@ pub fn run() {
             // Initialize Debug LiveView
             debug::init();
 
+            // Route geminicli:// links to the frontend
+            deep_link::init(app);
+
             // Open DevTools automatically in debug builds
             #[cfg(debug_assertions)]
             {
@@ -76,12 +101,21 @@ pub fn run() {
             commands::clear_approval_history,
             // Ollama commands
             ollama_commands::ollama_list_models,
+            ollama_commands::ollama_copy_model,
+            ollama_commands::ollama_running_models,
             ollama_commands::ollama_health_check,
             ollama_commands::ollama_generate,
             ollama_commands::ollama_generate_sync,
+            ollama_commands::ollama_generate_sync_json,
             ollama_commands::ollama_chat,
             ollama_commands::ollama_batch_generate,
+            ollama_commands::ollama_set_stream_coalescing,
             ollama_commands::get_cpu_info,
+            // Health
+            health::get_system_health,
+            // Response cache commands
+            response_cache::clear_response_cache,
+            response_cache::get_cache_stats,
             // Chat history commands
             chat_history::list_chat_sessions,
             chat_history::get_chat_session,
@@ -90,20 +124,41 @@ pub fn run() {
             chat_history::delete_chat_session,
             chat_history::update_chat_title,
             chat_history::clear_all_chats,
+            chat_history::fork_chat_session,
+            chat_history::list_session_forks,
+            chat_history::search_chat_sessions,
             // Agentic commands
             agentic::execute_command,
+            // Code language
+            code_language::detect_code_language,
             // Bridge IPC commands
             bridge::get_bridge_state,
             bridge::set_bridge_auto_approve,
             bridge::approve_bridge_request,
             bridge::reject_bridge_request,
             bridge::clear_bridge_requests,
+            bridge_server::start_bridge_server,
+            bridge_server::stop_bridge_server,
             // Memory commands
             memory::get_agent_memories,
             memory::add_agent_memory,
+            memory::add_agent_memory_auto,
+            memory::extract_graph_from_memories,
+            memory::update_agent_memory,
+            memory::delete_agent_memory,
             memory::clear_agent_memories,
+            memory::get_agent_memory_stats,
+            memory::get_all_agents,
+            memory::get_memory_stats,
+            memory::get_memory_settings,
+            memory::set_memory_settings,
             memory::get_knowledge_graph,
             memory::update_knowledge_graph,
+            memory::merge_knowledge_graphs,
+            memory::export_knowledge_graph,
+            memory::delete_knowledge_node,
+            memory::delete_knowledge_edge,
+            memory::update_knowledge_node,
             // Learning commands
             learning::learning_get_stats,
             learning::learning_get_preferences,
@@ -111,15 +166,27 @@ pub fn run() {
             learning::learning_rag_search,
             learning::learning_rag_add,
             learning::learning_rag_clear,
+            learning::learning_rag_list_sources,
+            learning::learning_rag_delete_by_source,
+            learning::learning_rag_reindex,
+            learning::learning_rag_cancel_reindex,
+            learning::learning_rag_reembed,
             learning::learning_collect_training,
             learning::learning_get_training_examples,
+            learning::learning_validate_training_data,
             learning::learning_export_for_finetune,
+            learning::learning_export_balanced,
+            learning::learning_get_training_distribution,
             learning::learning_pull_embedding_model,
+            learning::set_embedding_provider,
+            learning::get_current_embedding_provider,
             // Alzur (AI Trainer) commands
             learning::write_training_dataset,
             learning::start_model_training,
             learning::cancel_model_training,
             learning::get_alzur_models,
+            learning::get_training_jobs,
+            learning::get_training_job,
             // Debug LiveView commands
             debug::debug_get_stats,
             debug::debug_get_logs,
@@ -129,6 +196,16 @@ pub fn run() {
             debug::debug_add_log,
             debug::debug_start_streaming,
             debug::debug_stop_streaming,
+            // Logging commands
+            logging::set_log_level,
+            logging::get_log_path,
+            // Updates
+            updates::check_for_updates,
+            // Agent tools
+            tools::register_tool,
+            tools::list_tools,
+            tools::invoke_tool,
+            math::evaluate_math_expression,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
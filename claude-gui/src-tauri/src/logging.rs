@@ -0,0 +1,156 @@
+//! Logging setup: stderr plus a daily-rotated log file, with a level that can
+//! be changed at runtime.
+//!
+//! The initial filter comes from `RUST_LOG` (default `info`). Every line is
+//! passed through [`redact_secrets`] before it is written, so API keys and
+//! bearer tokens never reach the terminal or the log file. Events are also
+//! copied into the Debug LiveView buffer (see `debug_get_logs`).
+
+use parking_lot::Mutex;
+use regex::Regex;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+const LOG_FILE_PREFIX: &str = "claude-gui.log";
+
+lazy_static::lazy_static! {
+    static ref FILTER_HANDLE: Mutex<Option<reload::Handle<EnvFilter, Registry>>> = Mutex::new(None);
+    static ref SECRET_ASSIGNMENT: Regex = Regex::new(
+        r#"(?i)\b([a-z_]*(?:api[_-]?key|token|secret|password))(["']?\s*[:=]\s*["']?)[^\s"',}]+"#
+    )
+    .unwrap();
+    static ref BEARER_TOKEN: Regex = Regex::new(r"(?i)\bbearer\s+[A-Za-z0-9._~+/=-]+").unwrap();
+    static ref KNOWN_KEY_FORMAT: Regex =
+        Regex::new(r"\b(?:sk-[A-Za-z0-9_-]{16,}|AIza[0-9A-Za-z_-]{30,}|hf_[A-Za-z0-9]{20,})").unwrap();
+}
+
+/// Directory holding the rotated log files
+pub fn log_dir() -> PathBuf {
+    let mut path = dirs::data_local_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("claude-cli");
+    path.push("logs");
+    path
+}
+
+/// Mask anything that looks like a credential
+pub fn redact_secrets(line: &str) -> String {
+    let line = SECRET_ASSIGNMENT.replace_all(line, "$1$2[REDACTED]");
+    let line = BEARER_TOKEN.replace_all(&line, "Bearer [REDACTED]");
+    KNOWN_KEY_FORMAT.replace_all(&line, "[REDACTED]").into_owned()
+}
+
+/// Writer wrapper that redacts each formatted event before passing it on
+pub struct RedactingWriter<W: Write>(W);
+
+impl<W: Write> Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        self.0.write_all(redact_secrets(&text).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+pub struct Redacting<M>(M);
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for Redacting<M> {
+    type Writer = RedactingWriter<M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter(self.0.make_writer())
+    }
+}
+
+/// Install the global subscriber. Safe to call more than once.
+pub fn init() {
+    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let (filter, handle) = reload::Layer::new(env_filter);
+
+    let dir = log_dir();
+    let _ = std::fs::create_dir_all(&dir);
+    let file_appender = tracing_appender::rolling::daily(&dir, LOG_FILE_PREFIX);
+
+    let initialized = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer().with_writer(Redacting(io::stderr)))
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(Redacting(file_appender)),
+        )
+        .with(crate::debug::DebugLogLayer)
+        .try_init()
+        .is_ok();
+
+    if initialized {
+        *FILTER_HANDLE.lock() = Some(handle);
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Change the active log filter, e.g. `debug` or `info,claude_gui_lib=trace`
+#[tauri::command]
+pub fn set_log_level(level: String) -> Result<(), String> {
+    let filter = EnvFilter::try_new(&level).map_err(|e| format!("Invalid log level '{}': {}", level, e))?;
+
+    let handle = FILTER_HANDLE.lock();
+    let handle = handle.as_ref().ok_or("Logging not initialized")?;
+    handle
+        .reload(filter)
+        .map_err(|e| format!("Failed to set log level: {}", e))?;
+
+    tracing::info!("Log level set to {}", level);
+    Ok(())
+}
+
+/// Path of today's log file, for attaching to bug reports
+#[tauri::command]
+pub fn get_log_path() -> Result<String, String> {
+    let file_name = format!(
+        "{}.{}",
+        LOG_FILE_PREFIX,
+        chrono::Utc::now().format("%Y-%m-%d")
+    );
+    Ok(log_dir().join(file_name).to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacts_credentials() {
+        assert_eq!(
+            redact_secrets("GEMINI_API_KEY=abc123 done"),
+            "GEMINI_API_KEY=[REDACTED] done"
+        );
+        assert_eq!(
+            redact_secrets(r#"{"api_key": "xyz", "model": "m"}"#),
+            r#"{"api_key": "[REDACTED]", "model": "m"}"#
+        );
+        assert_eq!(
+            redact_secrets("Authorization: Bearer eyJhbGciOi.abc"),
+            "Authorization: Bearer [REDACTED]"
+        );
+        assert_eq!(
+            redact_secrets("using hf_abcdefghijklmnopqrstuvwx"),
+            "using [REDACTED]"
+        );
+    }
+
+    #[test]
+    fn test_leaves_plain_text() {
+        let line = "Loaded 12 tokens from cache";
+        assert_eq!(redact_secrets(line), line);
+    }
+}
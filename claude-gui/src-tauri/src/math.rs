@@ -0,0 +1,328 @@
+//! Calculator for agents
+//!
+//! Agents asked to compute things tend to guess. This evaluates arithmetic
+//! with a small recursive descent parser instead: numbers, `+ - * / ^`,
+//! parentheses and the functions `sqrt`, `log` (natural) and `abs`. Any other
+//! word or symbol is rejected, so nothing but arithmetic can be expressed.
+
+use std::time::Duration;
+
+const MAX_EXPRESSION_LEN: usize = 1000;
+/// Nesting limit for parentheses, unary signs and powers; keeps the
+/// recursion off the end of the stack
+const MAX_DEPTH: usize = 64;
+const EVAL_TIMEOUT: Duration = Duration::from_millis(100);
+const DEFAULT_PRECISION: u32 = 10;
+const MAX_PRECISION: u32 = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Number(f64),
+    Function(Function),
+    Op(char),
+    LParen,
+    RParen,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Function {
+    Sqrt,
+    Log,
+    Abs,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "sqrt" => Some(Self::Sqrt),
+            "log" => Some(Self::Log),
+            "abs" => Some(Self::Abs),
+            _ => None,
+        }
+    }
+
+    fn apply(self, x: f64) -> f64 {
+        match self {
+            Self::Sqrt => x.sqrt(),
+            Self::Log => x.ln(),
+            Self::Abs => x.abs(),
+        }
+    }
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '0'..='9' | '.' => {
+                let mut end = start;
+                while let Some(&(i, c)) = chars.peek() {
+                    if !(c.is_ascii_digit() || c == '.') {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                let literal = &expression[start..end];
+                let value = literal
+                    .parse()
+                    .map_err(|_| format!("Invalid number '{}'", literal))?;
+                tokens.push(Token::Number(value));
+            }
+            'a'..='z' | 'A'..='Z' => {
+                let mut end = start;
+                while let Some(&(i, c)) = chars.peek() {
+                    if !c.is_ascii_alphabetic() {
+                        break;
+                    }
+                    end = i + 1;
+                    chars.next();
+                }
+                let name = &expression[start..end];
+                let function = Function::from_name(name)
+                    .ok_or_else(|| format!("Unknown function '{}'", name))?;
+                tokens.push(Token::Function(function));
+            }
+            '+' | '-' | '*' | '/' | '^' => {
+                tokens.push(Token::Op(c));
+                chars.next();
+            }
+            '(' => {
+                tokens.push(Token::LParen);
+                chars.next();
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                chars.next();
+            }
+            other => return Err(format!("Unexpected character '{}'", other)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// expr    := term (('+' | '-') term)*
+/// term    := unary (('*' | '/') unary)*
+/// unary   := ('+' | '-') unary | power
+/// power   := primary ('^' unary)?
+/// primary := number | function '(' expr ')' | '(' expr ')'
+///
+/// `^` is right-associative and binds tighter than unary minus, so
+/// `-2^2` is -4 and `2^3^2` is 512.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<Token> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek();
+        self.pos += 1;
+        token
+    }
+
+    fn descend(&mut self) -> Result<(), String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err("Expression is nested too deeply".to_string());
+        }
+        Ok(())
+    }
+
+    fn expect_rparen(&mut self) -> Result<(), String> {
+        match self.next() {
+            Some(Token::RParen) => Ok(()),
+            _ => Err("Expected ')'".to_string()),
+        }
+    }
+
+    fn expr(&mut self) -> Result<f64, String> {
+        let mut value = self.term()?;
+        while let Some(Token::Op(op @ ('+' | '-'))) = self.peek() {
+            self.pos += 1;
+            let rhs = self.term()?;
+            value = if op == '+' { value + rhs } else { value - rhs };
+        }
+        Ok(value)
+    }
+
+    fn term(&mut self) -> Result<f64, String> {
+        let mut value = self.unary()?;
+        while let Some(Token::Op(op @ ('*' | '/'))) = self.peek() {
+            self.pos += 1;
+            let rhs = self.unary()?;
+            if op == '*' {
+                value *= rhs;
+            } else if rhs == 0.0 {
+                return Err("Division by zero".to_string());
+            } else {
+                value /= rhs;
+            }
+        }
+        Ok(value)
+    }
+
+    fn unary(&mut self) -> Result<f64, String> {
+        self.descend()?;
+        let value = match self.peek() {
+            Some(Token::Op('-')) => {
+                self.pos += 1;
+                -self.unary()?
+            }
+            Some(Token::Op('+')) => {
+                self.pos += 1;
+                self.unary()?
+            }
+            _ => self.power()?,
+        };
+        self.depth -= 1;
+        Ok(value)
+    }
+
+    fn power(&mut self) -> Result<f64, String> {
+        let base = self.primary()?;
+        if let Some(Token::Op('^')) = self.peek() {
+            self.pos += 1;
+            let exponent = self.unary()?;
+            return Ok(base.powf(exponent));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<f64, String> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(value),
+            Some(Token::Function(function)) => {
+                if self.next() != Some(Token::LParen) {
+                    return Err("Expected '(' after function name".to_string());
+                }
+                self.descend()?;
+                let arg = self.expr()?;
+                self.depth -= 1;
+                self.expect_rparen()?;
+                Ok(function.apply(arg))
+            }
+            Some(Token::LParen) => {
+                self.descend()?;
+                let value = self.expr()?;
+                self.depth -= 1;
+                self.expect_rparen()?;
+                Ok(value)
+            }
+            Some(token) => Err(format!("Unexpected {:?}", token)),
+            None => Err("Unexpected end of expression".to_string()),
+        }
+    }
+}
+
+/// Evaluate `expression`, failing on syntax errors and on results that are
+/// not finite (e.g. `sqrt(-1)` or an overflowing power)
+fn evaluate(expression: &str) -> Result<f64, String> {
+    if expression.len() > MAX_EXPRESSION_LEN {
+        return Err(format!(
+            "Expression is longer than {} characters",
+            MAX_EXPRESSION_LEN
+        ));
+    }
+
+    let tokens = tokenize(expression)?;
+    if tokens.is_empty() {
+        return Err("Empty expression".to_string());
+    }
+
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        depth: 0,
+    };
+    let value = parser.expr()?;
+    if let Some(token) = parser.peek() {
+        return Err(format!("Unexpected {:?}", token));
+    }
+    if !value.is_finite() {
+        return Err("Result is not a finite number".to_string());
+    }
+    Ok(value)
+}
+
+/// Round to `precision` decimal places (default 10, at most 15) and drop
+/// trailing zeros, so `0.1 + 0.2` reads as `0.3`
+pub fn format_math_result(value: f64, precision: Option<u32>) -> String {
+    let precision = precision.unwrap_or(DEFAULT_PRECISION).min(MAX_PRECISION) as usize;
+    let formatted = format!("{:.*}", precision, value);
+    let formatted = if formatted.contains('.') {
+        formatted.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        &formatted
+    };
+
+    if formatted == "-0" {
+        "0".to_string()
+    } else {
+        formatted.to_string()
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn evaluate_math_expression(expression: String) -> Result<f64, String> {
+    let task = tokio::task::spawn_blocking(move || evaluate(&expression));
+
+    tokio::time::timeout(EVAL_TIMEOUT, task)
+        .await
+        .map_err(|_| "Expression took too long to evaluate".to_string())?
+        .map_err(|e| format!("Evaluation failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate() {
+        assert_eq!(evaluate("1 + 2 * 3").unwrap(), 7.0);
+        assert_eq!(evaluate("(1 + 2) * 3").unwrap(), 9.0);
+        assert_eq!(evaluate("10 / 4 - 1").unwrap(), 1.5);
+        assert_eq!(evaluate("2^3^2").unwrap(), 512.0);
+        assert_eq!(evaluate("-2^2").unwrap(), -4.0);
+        assert_eq!(evaluate("2^-1").unwrap(), 0.5);
+        assert_eq!(evaluate("--3").unwrap(), 3.0);
+        assert_eq!(evaluate("sqrt(16) + abs(-2.5)").unwrap(), 6.5);
+        assert!((evaluate("log(2.718281828459045)").unwrap() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_evaluate_rejects_non_math() {
+        for expression in [
+            "", "2 +", "(1 + 2", "1 + 2)", "1 / 0", "sqrt(-1)", "x + 1", "exp(1)", "1; 2",
+            "sqrt 4", "1..2", "10^400",
+        ] {
+            assert!(evaluate(expression).is_err(), "{}", expression);
+        }
+        assert!(evaluate(&format!("{}1{}", "(".repeat(100), ")".repeat(100))).is_err());
+        assert!(evaluate(&"-".repeat(100)).is_err());
+    }
+
+    #[test]
+    fn test_format_math_result() {
+        assert_eq!(format_math_result(0.1 + 0.2, None), "0.3");
+        assert_eq!(format_math_result(2.0, None), "2");
+        assert_eq!(format_math_result(1.0 / 3.0, Some(3)), "0.333");
+        assert_eq!(format_math_result(-0.0000001, Some(2)), "0");
+        assert_eq!(format_math_result(1234.4, Some(0)), "1234");
+    }
+}
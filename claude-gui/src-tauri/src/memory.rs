@@ -1,6 +1,11 @@
+use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use crate::ollama_commands::OllamaState;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryEntry {
@@ -11,6 +16,9 @@ pub struct MemoryEntry {
     pub entry_type: String,
     pub content: String,
     pub tags: String,
+    /// 0.0 (trivia) to 1.0 (critical)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub importance: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -107,6 +115,32 @@ fn get_agent_memory_file(agent: &str) -> PathBuf {
     path
 }
 
+/// Total number of stored memories across all agents
+pub(crate) fn memory_entry_count() -> u32 {
+    let Ok(entries) = fs::read_dir(get_memories_path()) else {
+        return 0;
+    };
+
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().map(|e| e == "jsonl").unwrap_or(false))
+        .map(|path| {
+            fs::read_to_string(&path)
+                .map(|content| content.lines().filter(|l| !l.trim().is_empty()).count() as u32)
+                .unwrap_or(0)
+        })
+        .sum()
+}
+
+fn read_memory_file(path: &Path) -> Result<Vec<MemoryEntry>, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
 #[tauri::command]
 pub fn get_agent_memories(agent: String, limit: Option<u32>) -> Result<Vec<MemoryEntry>, String> {
     let path = get_agent_memory_file(&agent);
@@ -122,15 +156,12 @@ pub fn get_agent_memories(agent: String, limit: Option<u32>) -> Result<Vec<Memor
                 entry_type: "fact".to_string(),
                 content: format!("{} initialized. Ready for tasks.", agent),
                 tags: "init,system".to_string(),
+                importance: None,
             }
         ]);
     }
 
-    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
-    let mut entries: Vec<MemoryEntry> = content
-        .lines()
-        .filter_map(|line| serde_json::from_str(line).ok())
-        .collect();
+    let mut entries = read_memory_file(&path)?;
 
     // Sort by timestamp descending and limit
     entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
@@ -139,13 +170,276 @@ pub fn get_agent_memories(agent: String, limit: Option<u32>) -> Result<Vec<Memor
     Ok(entries)
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentMemoryStats {
+    pub agent: String,
+    pub total_entries: u32,
+    pub avg_importance: f32,
+    pub min_importance: f32,
+    pub max_importance: f32,
+    /// Unix milliseconds
+    pub oldest_entry_ts: Option<i64>,
+    pub newest_entry_ts: Option<i64>,
+    pub total_content_chars: u64,
+}
+
+fn compute_memory_stats(agent: String, entries: &[MemoryEntry]) -> AgentMemoryStats {
+    // Importance figures only cover entries that have a score
+    let scores: Vec<f32> = entries.iter().filter_map(|e| e.importance).collect();
+    let (avg_importance, min_importance, max_importance) = if scores.is_empty() {
+        (0.0, 0.0, 0.0)
+    } else {
+        (
+            scores.iter().sum::<f32>() / scores.len() as f32,
+            scores.iter().copied().fold(f32::INFINITY, f32::min),
+            scores.iter().copied().fold(f32::NEG_INFINITY, f32::max),
+        )
+    };
+
+    let timestamps: Vec<i64> = entries
+        .iter()
+        .filter_map(|e| chrono::DateTime::parse_from_rfc3339(&e.timestamp).ok())
+        .map(|ts| ts.timestamp_millis())
+        .collect();
+
+    AgentMemoryStats {
+        agent,
+        total_entries: entries.len() as u32,
+        avg_importance,
+        min_importance,
+        max_importance,
+        oldest_entry_ts: timestamps.iter().min().copied(),
+        newest_entry_ts: timestamps.iter().max().copied(),
+        total_content_chars: entries.iter().map(|e| e.content.chars().count() as u64).sum(),
+    }
+}
+
+#[tauri::command]
+pub fn get_agent_memory_stats(agent_name: String) -> Result<AgentMemoryStats, String> {
+    let path = get_agent_memory_file(&agent_name);
+    let entries = if path.exists() { read_memory_file(&path)? } else { Vec::new() };
+
+    Ok(compute_memory_stats(agent_name, &entries))
+}
+
+/// Names of all agents with stored memories, sorted
+#[tauri::command]
+pub fn get_all_agents() -> Result<Vec<String>, String> {
+    let dir = fs::read_dir(get_memories_path()).map_err(|e| e.to_string())?;
+    let mut agents = std::collections::BTreeSet::new();
+
+    for path in dir.flatten().map(|entry| entry.path()) {
+        if path.extension().map(|e| e != "jsonl").unwrap_or(true) {
+            continue;
+        }
+
+        // Files are named in lowercase; prefer the agent name as written in the entries
+        let name = match read_memory_file(&path)?.into_iter().next() {
+            Some(entry) => entry.agent,
+            None => path.file_stem().unwrap_or_default().to_string_lossy().to_string(),
+        };
+        agents.insert(name);
+    }
+
+    Ok(agents.into_iter().collect())
+}
+
+const DEFAULT_MAX_ENTRIES_PER_AGENT: u32 = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemorySettings {
+    /// Oldest entries beyond this are evicted, per agent
+    pub max_entries_per_agent: u32,
+}
+
+impl Default for MemorySettings {
+    fn default() -> Self {
+        Self {
+            max_entries_per_agent: DEFAULT_MAX_ENTRIES_PER_AGENT,
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref MEMORY_SETTINGS: RwLock<MemorySettings> = RwLock::new(load_memory_settings());
+    /// Held for every change to an agent memory file. Also caches how many
+    /// entries each file holds, so an append only rereads the file when it
+    /// pushes the agent over the cap.
+    static ref MEMORY_FILES: Mutex<HashMap<PathBuf, usize>> = Mutex::new(HashMap::new());
+    /// A number, optionally followed by "/ D" or "out of D"
+    static ref IMPORTANCE_SCORE: regex::Regex =
+        regex::Regex::new(r"(?i)(\d+(?:\.\d+)?|\.\d+)(?:\s*(?:/|out of)\s*(\d+(?:\.\d+)?))?").unwrap();
+}
+
+fn get_memory_settings_file() -> PathBuf {
+    let mut path = get_memories_path();
+    path.push("memory_settings.json");
+    path
+}
+
+fn load_memory_settings() -> MemorySettings {
+    fs::read_to_string(get_memory_settings_file())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Drop an agent's oldest entries beyond `max_entries`. Lines that don't
+/// parse are kept. Returns how many entries were evicted.
+fn evict_oldest(path: &Path, max_entries: usize) -> Result<usize, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let lines: Vec<&str> = content.lines().filter(|l| !l.trim().is_empty()).collect();
+
+    let mut dated: Vec<(usize, String)> = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| serde_json::from_str::<MemoryEntry>(line).ok().map(|e| (i, e.timestamp)))
+        .collect();
+    if dated.len() <= max_entries {
+        return Ok(0);
+    }
+
+    let excess = dated.len() - max_entries;
+    dated.sort_by(|a, b| a.1.cmp(&b.1));
+    let evicted: HashSet<usize> = dated.into_iter().take(excess).map(|(i, _)| i).collect();
+
+    let mut output: String = lines
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !evicted.contains(i))
+        .map(|(_, line)| *line)
+        .collect::<Vec<_>>()
+        .join("\n");
+    output.push('\n');
+    fs::write(path, output).map_err(|e| e.to_string())?;
+
+    Ok(excess)
+}
+
+/// Entries in a memory file, counted the way `evict_oldest` counts them
+fn count_entries(path: &Path) -> usize {
+    fs::read_to_string(path)
+        .map(|content| {
+            content
+                .lines()
+                .filter(|line| serde_json::from_str::<MemoryEntry>(line).is_ok())
+                .count()
+        })
+        .unwrap_or(0)
+}
+
+/// Append `entry` to `path`, evicting the oldest entries once there are more
+/// than `max_entries`. Returns how many entries were evicted.
+fn append_to_file(path: &Path, entry: &MemoryEntry, max_entries: usize) -> Result<usize, String> {
+    use std::io::Write;
+
+    let line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+    let mut counts = MEMORY_FILES.lock();
+    let count = counts.entry(path.to_path_buf()).or_insert_with(|| count_entries(path));
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| e.to_string())?;
+    writeln!(file, "{}", line).map_err(|e| e.to_string())?;
+    drop(file);
+
+    *count += 1;
+    if *count <= max_entries {
+        return Ok(0);
+    }
+
+    let evicted = evict_oldest(path, max_entries)?;
+    // Nothing to evict means the cached count was off; start over from the file
+    *count = if evicted > 0 { *count - evicted } else { count_entries(path) };
+    Ok(evicted)
+}
+
+fn append_memory(entry: &MemoryEntry) -> Result<(), String> {
+    let path = get_agent_memory_file(&entry.agent);
+
+    // The cap applies per agent, so one chatty agent can't evict another's memories
+    let max_entries = MEMORY_SETTINGS.read().max_entries_per_agent as usize;
+    let evicted = append_to_file(&path, entry, max_entries)?;
+    if evicted > 0 {
+        tracing::debug!("Evicted {} old memories for {}", evicted, entry.agent);
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_memory_settings() -> Result<MemorySettings, String> {
+    Ok(MEMORY_SETTINGS.read().clone())
+}
+
+/// Change the per-agent cap. Agents already above it are trimmed on their next write.
+#[tauri::command]
+pub fn set_memory_settings(settings: MemorySettings) -> Result<(), String> {
+    if settings.max_entries_per_agent == 0 {
+        return Err("max_entries_per_agent must be at least 1".to_string());
+    }
+
+    let content = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+    fs::write(get_memory_settings_file(), content).map_err(|e| e.to_string())?;
+    *MEMORY_SETTINGS.write() = settings;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryStoreStats {
+    pub agents: Vec<AgentMemoryStats>,
+    pub total_entries: u32,
+    pub total_size_bytes: u64,
+    /// Unix milliseconds
+    pub oldest_entry_ts: Option<i64>,
+    pub newest_entry_ts: Option<i64>,
+    pub max_entries_per_agent: u32,
+}
+
+/// Counts, disk usage and age range across every agent's memories
+#[tauri::command]
+pub fn get_memory_stats() -> Result<MemoryStoreStats, String> {
+    let dir = fs::read_dir(get_memories_path()).map_err(|e| e.to_string())?;
+    let mut agents = Vec::new();
+    let mut total_size_bytes = 0;
+
+    for path in dir.flatten().map(|entry| entry.path()) {
+        if path.extension().map(|e| e != "jsonl").unwrap_or(true) {
+            continue;
+        }
+
+        total_size_bytes += fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        let entries = read_memory_file(&path)?;
+        let agent = match entries.first() {
+            Some(entry) => entry.agent.clone(),
+            None => path.file_stem().unwrap_or_default().to_string_lossy().to_string(),
+        };
+        agents.push(compute_memory_stats(agent, &entries));
+    }
+    agents.sort_by(|a, b| a.agent.cmp(&b.agent));
+
+    Ok(MemoryStoreStats {
+        total_entries: agents.iter().map(|a| a.total_entries).sum(),
+        total_size_bytes,
+        oldest_entry_ts: agents.iter().filter_map(|a| a.oldest_entry_ts).min(),
+        newest_entry_ts: agents.iter().filter_map(|a| a.newest_entry_ts).max(),
+        max_entries_per_agent: MEMORY_SETTINGS.read().max_entries_per_agent,
+        agents,
+    })
+}
+
 #[tauri::command]
 pub fn add_agent_memory(
     agent: String,
     entry_type: String,
     content: String,
     tags: String,
+    importance: Option<f32>,
 ) -> Result<MemoryEntry, String> {
+    validate_importance(importance)?;
+
     let entry = MemoryEntry {
         id: uuid::Uuid::new_v4().to_string(),
         timestamp: chrono::Utc::now().to_rfc3339(),
@@ -153,20 +447,192 @@ pub fn add_agent_memory(
         entry_type,
         content,
         tags,
+        importance,
     };
 
-    let path = get_agent_memory_file(&agent);
-    let line = serde_json::to_string(&entry).map_err(|e| e.to_string())?;
+    append_memory(&entry)?;
 
-    // Append to file
-    use std::io::Write;
-    let mut file = fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&path)
-        .map_err(|e| e.to_string())?;
+    Ok(entry)
+}
 
-    writeln!(file, "{}", line).map_err(|e| e.to_string())?;
+fn validate_importance(importance: Option<f32>) -> Result<(), String> {
+    match importance {
+        Some(value) if !(0.0..=1.0).contains(&value) => {
+            Err(format!("Importance must be between 0 and 1, got {}", value))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Find the memory with `id` in any agent file and replace it with the result
+/// of `edit`, or remove it when `edit` returns `None`. Lines that don't parse
+/// are written back untouched. Returns whether a match was found.
+fn rewrite_memory_entry(
+    id: &str,
+    mut edit: impl FnMut(MemoryEntry) -> Option<MemoryEntry>,
+) -> Result<bool, String> {
+    // Held across the whole scan so appends and evictions can't interleave
+    let mut counts = MEMORY_FILES.lock();
+    let dir = fs::read_dir(get_memories_path()).map_err(|e| e.to_string())?;
+
+    for path in dir.flatten().map(|entry| entry.path()) {
+        if path.extension().map(|e| e != "jsonl").unwrap_or(true) {
+            continue;
+        }
+
+        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        let mut found = false;
+        let mut lines = Vec::new();
+
+        for line in content.lines() {
+            match serde_json::from_str::<MemoryEntry>(line) {
+                Ok(entry) if !found && entry.id == id => {
+                    found = true;
+                    if let Some(updated) = edit(entry) {
+                        lines.push(serde_json::to_string(&updated).map_err(|e| e.to_string())?);
+                    }
+                }
+                _ => lines.push(line.to_string()),
+            }
+        }
+
+        if found {
+            let mut output = lines.join("\n");
+            if !output.is_empty() {
+                output.push('\n');
+            }
+            fs::write(&path, output).map_err(|e| e.to_string())?;
+            // A delete changes the count; let the next append recount
+            counts.remove(&path);
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Edit a single memory by id. The original timestamp is kept unless
+/// `touch_timestamp` is set.
+#[tauri::command]
+pub fn update_agent_memory(
+    id: String,
+    mut content: Option<String>,
+    importance: Option<f32>,
+    touch_timestamp: Option<bool>,
+) -> Result<bool, String> {
+    validate_importance(importance)?;
+
+    rewrite_memory_entry(&id, |mut entry| {
+        if let Some(content) = content.take() {
+            entry.content = content;
+        }
+        if importance.is_some() {
+            entry.importance = importance;
+        }
+        if touch_timestamp.unwrap_or(false) {
+            entry.timestamp = chrono::Utc::now().to_rfc3339();
+        }
+        Some(entry)
+    })
+}
+
+#[tauri::command]
+pub fn delete_agent_memory(id: String) -> Result<bool, String> {
+    rewrite_memory_entry(&id, |_| None)
+}
+
+const IMPORTANCE_KEYWORDS: &[&str] = &[
+    "important", "critical", "always", "never", "must", "remember", "prefer",
+    "decision", "error", "bug", "deadline", "password", "security",
+];
+
+/// Keyword and length based importance, used when no model is available
+fn heuristic_importance(content: &str) -> f32 {
+    let lower = content.to_lowercase();
+    let keyword_hits = IMPORTANCE_KEYWORDS.iter().filter(|kw| lower.contains(*kw)).count();
+    let length_bonus = (content.chars().count() as f32 / 1000.0).min(0.2);
+
+    (0.3 + keyword_hits as f32 * 0.1 + length_bonus).clamp(0.0, 1.0)
+}
+
+/// Pull a 0-1 score out of a model reply such as "0.8", "Score: 7/10",
+/// "3 out of 5" or "85%". A bare number is scaled by its magnitude.
+fn parse_importance_score(reply: &str) -> Option<f32> {
+    let captures = IMPORTANCE_SCORE.captures(reply)?;
+    let value: f32 = captures[1].parse().ok()?;
+
+    if let Some(denominator) = captures.get(2) {
+        let denominator: f32 = denominator.as_str().parse().ok()?;
+        if denominator <= 0.0 || value > denominator {
+            return None;
+        }
+        return Some(value / denominator);
+    }
+
+    let score = if value <= 1.0 {
+        value
+    } else if value <= 10.0 {
+        value / 10.0
+    } else if value <= 100.0 {
+        value / 100.0
+    } else {
+        return None;
+    };
+
+    Some(score.clamp(0.0, 1.0))
+}
+
+async fn score_with_model(state: &OllamaState, model: Option<String>, content: &str) -> Option<f32> {
+    let client = state.client.read().await;
+
+    let model = match model {
+        Some(model) => model,
+        None => client
+            .list_models()
+            .await
+            .ok()?
+            .into_iter()
+            .map(|m| m.name)
+            .find(|name| !name.contains("embed"))?,
+    };
+
+    let prompt = format!(
+        "Rate how important the following memory is for an AI assistant to retain long-term, \
+         on a scale from 0 (trivial) to 1 (critical). Reply with only the number.\n\nMemory: {}",
+        crate::utils::truncate_chars(content, 2000)
+    );
+
+    let reply = client.generate_sync(&model, &prompt, None).await.ok()?;
+    parse_importance_score(&reply)
+}
+
+/// Add a memory and let the local model score its importance.
+/// Falls back to a heuristic when Ollama is unavailable or the reply can't be parsed.
+#[tauri::command]
+pub async fn add_agent_memory_auto(
+    state: State<'_, OllamaState>,
+    agent: String,
+    content: String,
+    entry_type: Option<String>,
+    tags: Option<String>,
+    model: Option<String>,
+) -> Result<MemoryEntry, String> {
+    let importance = match score_with_model(&state, model, &content).await {
+        Some(score) => score,
+        None => heuristic_importance(&content),
+    };
+
+    let entry = MemoryEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        agent,
+        entry_type: entry_type.unwrap_or_else(|| "fact".to_string()),
+        content,
+        tags: tags.unwrap_or_default(),
+        importance: Some(importance),
+    };
+
+    append_memory(&entry)?;
 
     Ok(entry)
 }
@@ -174,18 +640,30 @@ pub fn add_agent_memory(
 #[tauri::command]
 pub fn clear_agent_memories(agent: String) -> Result<(), String> {
     let path = get_agent_memory_file(&agent);
+    let mut counts = MEMORY_FILES.lock();
 
     if path.exists() {
         fs::remove_file(&path).map_err(|e| e.to_string())?;
     }
+    counts.remove(&path);
 
     Ok(())
 }
 
-#[tauri::command]
-pub fn get_knowledge_graph() -> Result<KnowledgeGraph, String> {
+fn get_knowledge_graph_file() -> PathBuf {
     let mut path = get_memories_path();
     path.push("knowledge_graph.json");
+    path
+}
+
+fn save_knowledge_graph(graph: &KnowledgeGraph) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(graph).map_err(|e| e.to_string())?;
+    fs::write(get_knowledge_graph_file(), content).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_knowledge_graph() -> Result<KnowledgeGraph, String> {
+    let path = get_knowledge_graph_file();
 
     if path.exists() {
         let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
@@ -199,11 +677,600 @@ pub fn get_knowledge_graph() -> Result<KnowledgeGraph, String> {
 
 #[tauri::command]
 pub fn update_knowledge_graph(graph: KnowledgeGraph) -> Result<(), String> {
+    save_knowledge_graph(&graph)
+}
+
+/// Number of nodes and edges touched by a graph edit
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct GraphEditResult {
+    pub nodes: u32,
+    pub edges: u32,
+}
+
+/// Remove a node and every edge touching it
+fn remove_node(graph: &mut KnowledgeGraph, id: &str) -> GraphEditResult {
+    let (nodes_before, edges_before) = (graph.nodes.len(), graph.edges.len());
+    graph.nodes.retain(|n| n.id != id);
+    graph.edges.retain(|e| e.source != id && e.target != id);
+
+    GraphEditResult {
+        nodes: (nodes_before - graph.nodes.len()) as u32,
+        edges: (edges_before - graph.edges.len()) as u32,
+    }
+}
+
+/// Delete a node and its incident edges. Deleting a missing node is not an error.
+#[tauri::command]
+pub fn delete_knowledge_node(id: String) -> Result<GraphEditResult, String> {
+    let mut graph = get_knowledge_graph()?;
+    let result = remove_node(&mut graph, &id);
+    if result.nodes > 0 || result.edges > 0 {
+        save_knowledge_graph(&graph)?;
+    }
+    Ok(result)
+}
+
+#[tauri::command]
+pub fn delete_knowledge_edge(source: String, target: String, label: String) -> Result<GraphEditResult, String> {
+    let mut graph = get_knowledge_graph()?;
+    let before = graph.edges.len();
+    graph
+        .edges
+        .retain(|e| !(e.source == source && e.target == target && e.label == label));
+
+    let removed = (before - graph.edges.len()) as u32;
+    if removed > 0 {
+        save_knowledge_graph(&graph)?;
+    }
+    Ok(GraphEditResult { nodes: 0, edges: removed })
+}
+
+/// Change a node's label and/or type; fields left as `None` are kept
+#[tauri::command]
+pub fn update_knowledge_node(
+    id: String,
+    label: Option<String>,
+    node_type: Option<String>,
+) -> Result<GraphEditResult, String> {
+    let mut graph = get_knowledge_graph()?;
+    let Some(node) = graph.nodes.iter_mut().find(|n| n.id == id) else {
+        return Ok(GraphEditResult { nodes: 0, edges: 0 });
+    };
+
+    if label.is_some() {
+        node.label = label;
+    }
+    if let Some(node_type) = node_type {
+        node.node_type = node_type;
+    }
+
+    save_knowledge_graph(&graph)?;
+    Ok(GraphEditResult { nodes: 1, edges: 0 })
+}
+
+/// Per-agent graph file, merged into the shared graph by `merge_knowledge_graphs`
+fn get_agent_graph_file(agent: &str) -> PathBuf {
     let mut path = get_memories_path();
-    path.push("knowledge_graph.json");
+    path.push(format!("agent_graph_{}.json", agent.to_lowercase()));
+    path
+}
 
-    let content = serde_json::to_string_pretty(&graph).map_err(|e| e.to_string())?;
-    fs::write(&path, content).map_err(|e| e.to_string())?;
+/// Number of populated fields, used to pick the richer of two duplicate nodes
+fn node_completeness(node: &KnowledgeNode) -> u8 {
+    !node.node_type.is_empty() as u8 + node.label.is_some() as u8
+}
 
-    Ok(())
+/// Merge `other` into `graph`, deduplicating nodes by id and edges by
+/// `(source, target, label)`
+fn merge_graph_into(graph: &mut KnowledgeGraph, other: KnowledgeGraph) {
+    for node in other.nodes {
+        match graph.nodes.iter_mut().find(|n| n.id == node.id) {
+            Some(existing) => {
+                if node_completeness(&node) > node_completeness(existing) {
+                    *existing = node;
+                }
+            }
+            None => graph.nodes.push(node),
+        }
+    }
+
+    for edge in other.edges {
+        let exists = graph
+            .edges
+            .iter()
+            .any(|e| e.source == edge.source && e.target == edge.target && e.label == edge.label);
+        if !exists {
+            graph.edges.push(edge);
+        }
+    }
+}
+
+/// Combine the shared graph with the per-agent graphs of `source_agents`.
+/// Agents without a graph file are skipped. Saved as the shared graph when `persist` is set.
+#[tauri::command]
+pub fn merge_knowledge_graphs(source_agents: Vec<String>, persist: Option<bool>) -> Result<KnowledgeGraph, String> {
+    let mut merged = get_knowledge_graph()?;
+
+    for agent in &source_agents {
+        let path = get_agent_graph_file(agent);
+        if !path.exists() {
+            continue;
+        }
+
+        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        let graph: KnowledgeGraph =
+            serde_json::from_str(&content).map_err(|e| format!("Invalid graph for {}: {}", agent, e))?;
+        merge_graph_into(&mut merged, graph);
+    }
+
+    if persist.unwrap_or(false) {
+        save_knowledge_graph(&merged)?;
+    }
+
+    Ok(merged)
+}
+
+/// Output format of `export_knowledge_graph`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphExportFormat {
+    Dot,
+    GraphML,
+}
+
+/// Fill colours for node types, picked by a stable hash of the type name
+const NODE_TYPE_COLORS: &[&str] = &[
+    "#8dd3c7", "#ffffb3", "#bebada", "#fb8072", "#80b1d3", "#fdb462", "#b3de69", "#fccde5",
+];
+
+fn node_type_color(node_type: &str) -> &'static str {
+    let hash = node_type.bytes().fold(0usize, |acc, b| acc.wrapping_mul(31).wrapping_add(b as usize));
+    NODE_TYPE_COLORS[hash % NODE_TYPE_COLORS.len()]
+}
+
+/// Escape for a double-quoted DOT string
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "")
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn graph_to_dot(graph: &KnowledgeGraph) -> String {
+    let mut out = String::from("digraph KnowledgeGraph {\n    node [style=filled];\n");
+
+    for node in &graph.nodes {
+        out.push_str(&format!(
+            "    \"{}\" [label=\"{}\", class=\"{}\", fillcolor=\"{}\"];\n",
+            escape_dot(&node.id),
+            escape_dot(node.label.as_deref().unwrap_or(&node.id)),
+            escape_dot(&node.node_type),
+            node_type_color(&node.node_type),
+        ));
+    }
+    for edge in &graph.edges {
+        out.push_str(&format!(
+            "    \"{}\" -> \"{}\" [label=\"{}\"];\n",
+            escape_dot(&edge.source),
+            escape_dot(&edge.target),
+            escape_dot(&edge.label),
+        ));
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+fn graph_to_graphml(graph: &KnowledgeGraph) -> String {
+    let mut out = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+        "  <key id=\"label\" for=\"all\" attr.name=\"label\" attr.type=\"string\"/>\n",
+        "  <key id=\"type\" for=\"node\" attr.name=\"type\" attr.type=\"string\"/>\n",
+        "  <key id=\"color\" for=\"node\" attr.name=\"color\" attr.type=\"string\"/>\n",
+        "  <graph id=\"KnowledgeGraph\" edgedefault=\"directed\">\n",
+    ));
+
+    for node in &graph.nodes {
+        out.push_str(&format!(
+            concat!(
+                "    <node id=\"{}\">\n",
+                "      <data key=\"label\">{}</data>\n",
+                "      <data key=\"type\">{}</data>\n",
+                "      <data key=\"color\">{}</data>\n",
+                "    </node>\n",
+            ),
+            escape_xml(&node.id),
+            escape_xml(node.label.as_deref().unwrap_or(&node.id)),
+            escape_xml(&node.node_type),
+            node_type_color(&node.node_type),
+        ));
+    }
+    for (i, edge) in graph.edges.iter().enumerate() {
+        out.push_str(&format!(
+            "    <edge id=\"e{}\" source=\"{}\" target=\"{}\">\n      <data key=\"label\">{}</data>\n    </edge>\n",
+            i,
+            escape_xml(&edge.source),
+            escape_xml(&edge.target),
+            escape_xml(&edge.label),
+        ));
+    }
+
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+/// Serialize the shared knowledge graph for rendering outside the app
+#[tauri::command]
+pub fn export_knowledge_graph(format: GraphExportFormat) -> Result<String, String> {
+    let graph = get_knowledge_graph()?;
+    Ok(match format {
+        GraphExportFormat::Dot => graph_to_dot(&graph),
+        GraphExportFormat::GraphML => graph_to_graphml(&graph),
+    })
+}
+
+// ============================================================================
+// Knowledge graph extraction
+// ============================================================================
+
+/// Upper bound on memories sent to the model per extraction call
+const MAX_MEMORIES_PER_EXTRACTION: usize = 20;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphExtractionResult {
+    pub memories_processed: u32,
+    pub nodes_added: u32,
+    pub edges_added: u32,
+}
+
+/// Memory ids already run through extraction, so re-running skips them
+fn get_extracted_ids_file() -> PathBuf {
+    let mut path = get_memories_path();
+    path.push("knowledge_graph_extracted.json");
+    path
+}
+
+fn load_extracted_ids() -> HashSet<String> {
+    fs::read_to_string(get_extracted_ids_file())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Parse `subject | relation | object` lines, ignoring anything else the model says
+fn parse_triples(reply: &str) -> Vec<(String, String, String)> {
+    reply
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim().trim_start_matches(['-', '*']).trim();
+            let parts: Vec<&str> = line.split('|').map(str::trim).collect();
+            match parts.as_slice() {
+                [subject, relation, object]
+                    if !subject.is_empty() && !relation.is_empty() && !object.is_empty() =>
+                {
+                    Some((subject.to_string(), relation.to_string(), object.to_string()))
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Find a node by id ignoring case, adding it if missing. Returns the stored id
+/// and whether a node was added.
+fn ensure_node(graph: &mut KnowledgeGraph, name: &str) -> (String, bool) {
+    if let Some(node) = graph.nodes.iter().find(|n| n.id.eq_ignore_ascii_case(name)) {
+        return (node.id.clone(), false);
+    }
+
+    graph.nodes.push(KnowledgeNode {
+        id: name.to_string(),
+        node_type: "entity".to_string(),
+        label: Some(name.to_string()),
+    });
+    (name.to_string(), true)
+}
+
+/// Merge triples into the graph, skipping edges that already exist.
+/// Returns the number of nodes and edges added.
+fn merge_triples(graph: &mut KnowledgeGraph, triples: &[(String, String, String)]) -> (u32, u32) {
+    let mut nodes_added = 0;
+    let mut edges_added = 0;
+
+    for (subject, relation, object) in triples {
+        let (source, source_added) = ensure_node(graph, subject);
+        let (target, target_added) = ensure_node(graph, object);
+        nodes_added += source_added as u32 + target_added as u32;
+
+        let label = relation.to_lowercase().replace(' ', "_");
+        let exists = graph
+            .edges
+            .iter()
+            .any(|e| e.source == source && e.target == target && e.label == label);
+        if !exists {
+            graph.edges.push(KnowledgeEdge { source, target, label });
+            edges_added += 1;
+        }
+    }
+
+    (nodes_added, edges_added)
+}
+
+/// Run an agent's memories through the local model to extract entities and
+/// relations into the knowledge graph. Memories already processed are skipped.
+#[tauri::command]
+pub async fn extract_graph_from_memories(
+    state: State<'_, OllamaState>,
+    agent: String,
+    model: Option<String>,
+) -> Result<GraphExtractionResult, String> {
+    let mut result = GraphExtractionResult {
+        memories_processed: 0,
+        nodes_added: 0,
+        edges_added: 0,
+    };
+
+    // Without a file get_agent_memories returns a placeholder entry, not real memories
+    if !get_agent_memory_file(&agent).exists() {
+        return Ok(result);
+    }
+
+    let mut extracted = load_extracted_ids();
+    let pending: Vec<MemoryEntry> = get_agent_memories(agent, Some(u32::MAX))?
+        .into_iter()
+        .filter(|m| !extracted.contains(&m.id))
+        .take(MAX_MEMORIES_PER_EXTRACTION)
+        .collect();
+
+    if pending.is_empty() {
+        return Ok(result);
+    }
+
+    let client = state.client.read().await;
+    let model = match model {
+        Some(model) => model,
+        None => client
+            .list_models()
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|m| m.name)
+            .find(|name| !name.contains("embed"))
+            .ok_or("No chat model available in Ollama")?,
+    };
+
+    let mut graph = get_knowledge_graph()?;
+
+    for memory in &pending {
+        let prompt = format!(
+            "Extract the entities and relations from the text below as triples, one per line, \
+             in the form `subject | relation | object`. Use short names. \
+             Reply with only the triples, or nothing if there are none.\n\nText: {}",
+            crate::utils::truncate_chars(&memory.content, 2000)
+        );
+
+        let reply = client
+            .generate_sync(&model, &prompt, None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let (nodes, edges) = merge_triples(&mut graph, &parse_triples(&reply));
+        result.nodes_added += nodes;
+        result.edges_added += edges;
+        result.memories_processed += 1;
+        extracted.insert(memory.id.clone());
+    }
+
+    save_knowledge_graph(&graph)?;
+    let ids = serde_json::to_string(&extracted).map_err(|e| e.to_string())?;
+    fs::write(get_extracted_ids_file(), ids).map_err(|e| e.to_string())?;
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graph_export_escaping() {
+        let graph = KnowledgeGraph {
+            nodes: vec![KnowledgeNode {
+                id: "a\"b".to_string(),
+                node_type: "tool".to_string(),
+                label: Some("<Rust & \"Tauri\">\nv2".to_string()),
+            }],
+            edges: vec![KnowledgeEdge {
+                source: "a\"b".to_string(),
+                target: "c".to_string(),
+                label: "uses \\ 'x'".to_string(),
+            }],
+        };
+
+        let dot = graph_to_dot(&graph);
+        assert!(dot.contains(r#""a\"b" [label="<Rust & \"Tauri\">\nv2", class="tool""#));
+        assert!(dot.contains(r#""a\"b" -> "c" [label="uses \\ 'x'"]"#));
+
+        let graphml = graph_to_graphml(&graph);
+        assert!(graphml.contains(r#"<node id="a&quot;b">"#));
+        assert!(graphml.contains("&lt;Rust &amp; &quot;Tauri&quot;&gt;"));
+        assert!(graphml.contains("uses \\ &apos;x&apos;"));
+    }
+
+    #[test]
+    fn test_parse_importance_score() {
+        assert_eq!(parse_importance_score("0.8"), Some(0.8));
+        assert_eq!(parse_importance_score("Score: 7/10"), Some(0.7));
+        assert_eq!(parse_importance_score("1/10"), Some(0.1));
+        assert_eq!(parse_importance_score("1 out of 10"), Some(0.1));
+        assert_eq!(parse_importance_score("3 Out Of 5"), Some(0.6));
+        assert_eq!(parse_importance_score("7 / 100"), Some(0.07));
+        assert_eq!(parse_importance_score("12/10"), None);
+        assert_eq!(parse_importance_score("1/0"), None);
+        assert_eq!(parse_importance_score("85%"), Some(0.85));
+        assert_eq!(parse_importance_score(".5"), Some(0.5));
+        assert_eq!(parse_importance_score("no idea"), None);
+        assert_eq!(parse_importance_score("9000"), None);
+    }
+
+    #[test]
+    fn test_heuristic_importance_in_range() {
+        let low = heuristic_importance("ok");
+        let high = heuristic_importance("Important: never push to main, always remember the security review");
+        assert!((0.0..=1.0).contains(&low));
+        assert!((0.0..=1.0).contains(&high));
+        assert!(high > low);
+    }
+
+    #[test]
+    fn test_compute_memory_stats() {
+        let entry = |timestamp: &str, content: &str, importance: Option<f32>| MemoryEntry {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: timestamp.to_string(),
+            agent: "Geralt".to_string(),
+            entry_type: "fact".to_string(),
+            content: content.to_string(),
+            tags: String::new(),
+            importance,
+        };
+        let entries = vec![
+            entry("2025-01-01T00:00:00Z", "żółw", Some(0.2)),
+            entry("2025-01-02T00:00:00Z", "abc", None),
+            entry("2025-01-03T00:00:00Z", "de", Some(0.6)),
+        ];
+
+        let stats = compute_memory_stats("Geralt".to_string(), &entries);
+        assert_eq!(stats.total_entries, 3);
+        assert!((stats.avg_importance - 0.4).abs() < 1e-6);
+        assert_eq!(stats.min_importance, 0.2);
+        assert_eq!(stats.max_importance, 0.6);
+        assert_eq!(stats.oldest_entry_ts, Some(1_735_689_600_000));
+        assert_eq!(stats.newest_entry_ts, Some(1_735_862_400_000));
+        assert_eq!(stats.total_content_chars, 9);
+    }
+
+    #[test]
+    fn test_evict_oldest_keeps_newest() {
+        let path = std::env::temp_dir().join(format!("memory-evict-{}.jsonl", uuid::Uuid::new_v4()));
+        let line = |ts: &str| {
+            format!(
+                r#"{{"id":"{ts}","timestamp":"{ts}","agent":"A","type":"fact","content":"c","tags":""}}"#
+            )
+        };
+        let content = [line("2025-01-03"), "not json".to_string(), line("2025-01-01"), line("2025-01-02")].join("\n");
+        fs::write(&path, content).unwrap();
+
+        assert_eq!(evict_oldest(&path, 2).unwrap(), 1);
+        let remaining = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(!remaining.contains("2025-01-01"));
+        assert!(remaining.contains("2025-01-02") && remaining.contains("2025-01-03"));
+        assert!(remaining.contains("not json"));
+    }
+
+    #[test]
+    fn test_concurrent_appends_keep_every_entry() {
+        let path = std::env::temp_dir().join(format!("memory-append-{}.jsonl", uuid::Uuid::new_v4()));
+        let entry = |i: usize| MemoryEntry {
+            id: i.to_string(),
+            timestamp: format!("2025-01-01T00:00:{:02}Z", i),
+            agent: "A".to_string(),
+            entry_type: "fact".to_string(),
+            content: "c".to_string(),
+            tags: String::new(),
+            importance: None,
+        };
+
+        std::thread::scope(|scope| {
+            for t in 0..4 {
+                let (path, entry) = (&path, &entry);
+                scope.spawn(move || {
+                    for i in 0..10 {
+                        append_to_file(path, &entry(t * 10 + i), 100).unwrap();
+                    }
+                });
+            }
+        });
+        assert_eq!(count_entries(&path), 40);
+
+        // Only appends that go over the cap evict
+        assert_eq!(append_to_file(&path, &entry(40), 41).unwrap(), 0);
+        assert_eq!(append_to_file(&path, &entry(41), 41).unwrap(), 1);
+        assert_eq!(count_entries(&path), 41);
+
+        MEMORY_FILES.lock().remove(&path);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_remove_node_cascades() {
+        let mut graph = KnowledgeGraph::default();
+        assert_eq!(remove_node(&mut graph, "Tauri"), GraphEditResult { nodes: 1, edges: 2 });
+        assert!(graph.edges.iter().all(|e| e.source != "Tauri" && e.target != "Tauri"));
+        assert_eq!(remove_node(&mut graph, "Tauri"), GraphEditResult { nodes: 0, edges: 0 });
+    }
+
+    #[test]
+    fn test_merge_graph_dedupes() {
+        let node = |id: &str, label: Option<&str>| KnowledgeNode {
+            id: id.to_string(),
+            node_type: "entity".to_string(),
+            label: label.map(str::to_string),
+        };
+        let edge = |source: &str, target: &str| KnowledgeEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            label: "uses".to_string(),
+        };
+
+        let mut graph = KnowledgeGraph {
+            nodes: vec![node("Rust", None)],
+            edges: vec![edge("Tauri", "Rust")],
+        };
+        merge_graph_into(
+            &mut graph,
+            KnowledgeGraph {
+                nodes: vec![node("Rust", Some("Rust 2021")), node("Tauri", None)],
+                edges: vec![edge("Tauri", "Rust"), edge("Rust", "Tauri")],
+            },
+        );
+
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.nodes[0].label.as_deref(), Some("Rust 2021"));
+        assert_eq!(graph.edges.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_triples() {
+        let reply = "Here you go:\n- Tauri | powered_by | Rust\nReact|used by|ClaudeHydra\nbad line\n | x | y";
+        assert_eq!(
+            parse_triples(reply),
+            vec![
+                ("Tauri".to_string(), "powered_by".to_string(), "Rust".to_string()),
+                ("React".to_string(), "used by".to_string(), "ClaudeHydra".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_triples_is_idempotent() {
+        let mut graph = KnowledgeGraph::default();
+        let triples = vec![
+            ("tauri".to_string(), "Powered By".to_string(), "Rust".to_string()),
+            ("Ollama".to_string(), "runs".to_string(), "Llama".to_string()),
+        ];
+
+        assert_eq!(merge_triples(&mut graph, &triples), (2, 1));
+        assert_eq!(merge_triples(&mut graph, &triples), (0, 0));
+    }
 }
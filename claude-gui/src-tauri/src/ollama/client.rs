@@ -1,14 +1,141 @@
-use futures_util::StreamExt;
 use reqwest::Client;
-use tauri::{Emitter, Window};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Window};
 
+use super::ndjson::{parse_line, NdjsonReader};
 use super::types::*;
+use crate::error::AppError;
+use crate::response_cache;
 
 const DEFAULT_OLLAMA_URL: &str = "http://127.0.0.1:11434";
 
+/// Tracks elapsed time and decode throughput for a streaming response
+struct StreamTiming {
+    started: Instant,
+    first_token_at: Option<Instant>,
+    tokens: u64,
+}
+
+impl StreamTiming {
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            first_token_at: None,
+            tokens: 0,
+        }
+    }
+
+    fn record(&mut self, token: &str) {
+        if token.is_empty() {
+            return;
+        }
+        self.first_token_at.get_or_insert_with(Instant::now);
+        self.tokens += 1;
+    }
+
+    fn elapsed_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+
+    /// Tokens per second since the first token, so prompt evaluation
+    /// time doesn't drag the figure down
+    fn tokens_per_sec(&self) -> f64 {
+        match self.first_token_at {
+            Some(first) if self.tokens > 1 => {
+                let secs = first.elapsed().as_secs_f64();
+                if secs > 0.0 {
+                    (self.tokens - 1) as f64 / secs
+                } else {
+                    0.0
+                }
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+/// Who receives `ollama-stream-chunk` events
+pub enum StreamTarget {
+    /// Every window; listeners tell streams apart by [`StreamChunk::id`]
+    All(AppHandle),
+    /// Only the window that started the request
+    Window(Window),
+}
+
+impl StreamTarget {
+    fn emit_chunk(&self, chunk: &StreamChunk) {
+        let _ = match self {
+            StreamTarget::All(app) => app.emit("ollama-stream-chunk", chunk),
+            StreamTarget::Window(window) => window.emit_to(window.label(), "ollama-stream-chunk", chunk),
+        };
+    }
+}
+
+/// Accumulates streamed tokens so the UI gets fewer, larger events
+struct TokenCoalescer {
+    pending: String,
+    pending_tokens: u32,
+    last_flush: Instant,
+    flush_interval: Duration,
+    max_tokens: u32,
+}
+
+impl TokenCoalescer {
+    fn new(settings: StreamCoalescing) -> Self {
+        Self {
+            pending: String::new(),
+            pending_tokens: 0,
+            last_flush: Instant::now(),
+            flush_interval: Duration::from_millis(settings.flush_interval_ms),
+            max_tokens: settings.max_tokens.max(1),
+        }
+    }
+
+    /// Buffer a token, returning the batched text when it is time to emit
+    fn push(&mut self, token: &str) -> Option<String> {
+        if !token.is_empty() {
+            self.pending.push_str(token);
+            self.pending_tokens += 1;
+        }
+
+        let due = self.pending_tokens >= self.max_tokens || self.last_flush.elapsed() >= self.flush_interval;
+        if due && self.pending_tokens > 0 {
+            Some(self.take())
+        } else {
+            None
+        }
+    }
+
+    /// Put text ahead of the first batch without counting it as a token
+    fn seed(&mut self, text: &str) {
+        self.pending.insert_str(0, text);
+    }
+
+    /// Text to emit for a streamed token, if any. On the `done` chunk this is
+    /// everything still buffered, including a batch `push` just released.
+    fn next_batch(&mut self, token: &str, done: bool) -> Option<String> {
+        match (self.push(token), done) {
+            (Some(batch), true) => Some(batch + &self.take()),
+            (None, true) => Some(self.take()),
+            (Some(batch), false) => Some(batch),
+            (None, false) => None,
+        }
+    }
+
+    /// Everything buffered so far; used for the final `done` chunk
+    fn take(&mut self) -> String {
+        self.pending_tokens = 0;
+        self.last_flush = Instant::now();
+        std::mem::take(&mut self.pending)
+    }
+}
+
 pub struct OllamaClient {
     client: Client,
     base_url: String,
+    /// Separate from the client's own lock so changing it never waits on
+    /// running streams; each stream reads it once when it starts
+    coalescing: parking_lot::RwLock<StreamCoalescing>,
 }
 
 impl OllamaClient {
@@ -16,11 +143,21 @@ impl OllamaClient {
         Self {
             client: Client::new(),
             base_url: base_url.unwrap_or_else(|| DEFAULT_OLLAMA_URL.to_string()),
+            coalescing: parking_lot::RwLock::new(StreamCoalescing::default()),
         }
     }
 
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Applies to streams started afterwards
+    pub fn set_stream_coalescing(&self, coalescing: StreamCoalescing) {
+        *self.coalescing.write() = coalescing;
+    }
+
     /// List available models
-    pub async fn list_models(&self) -> Result<Vec<OllamaModel>, String> {
+    pub async fn list_models(&self) -> Result<Vec<OllamaModel>, AppError> {
         let url = format!("{}/api/tags", self.base_url);
 
         let response = self
@@ -28,29 +165,71 @@ impl OllamaClient {
             .get(&url)
             .send()
             .await
-            .map_err(|e| format!("Failed to connect to Ollama: {}", e))?;
+            .map_err(|e| AppError::OllamaUnavailable(e.to_string()))?;
 
         if !response.status().is_success() {
-            return Err(format!("Ollama API error: {}", response.status()));
+            return Err(AppError::OllamaApi(response.status().to_string()));
         }
 
         let models: OllamaModelsResponse = response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse response: {}", e))?;
+            .map_err(|e| AppError::InvalidResponse(e.to_string()))?;
 
         Ok(models.models)
     }
 
+    /// Clone a model under a new name
+    pub async fn copy_model(&self, source: &str, destination: &str) -> Result<(), AppError> {
+        let url = format!("{}/api/copy", self.base_url);
+
+        let response = self
+            .client
+            .post(&url)
+            .json(&serde_json::json!({ "source": source, "destination": destination }))
+            .send()
+            .await
+            .map_err(|e| AppError::OllamaUnavailable(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(status_error(response.status(), source));
+        }
+
+        Ok(())
+    }
+
+    /// Models currently loaded in memory
+    pub async fn running_models(&self) -> Result<Vec<OllamaRunningModel>, AppError> {
+        let url = format!("{}/api/ps", self.base_url);
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| AppError::OllamaUnavailable(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::OllamaApi(response.status().to_string()));
+        }
+
+        let running: OllamaRunningModelsResponse = response
+            .json()
+            .await
+            .map_err(|e| AppError::InvalidResponse(e.to_string()))?;
+
+        Ok(running.models)
+    }
+
     /// Generate completion with streaming
     pub async fn generate_stream(
         &self,
-        window: &Window,
+        target: &StreamTarget,
         request_id: &str,
         model: &str,
         prompt: &str,
         system: Option<String>,
-    ) -> Result<String, String> {
+    ) -> Result<String, AppError> {
         let url = format!("{}/api/generate", self.base_url);
 
         let request = OllamaRequest {
@@ -67,68 +246,99 @@ impl OllamaClient {
             .json(&request)
             .send()
             .await
-            .map_err(|e| format!("Failed to connect to Ollama: {}", e))?;
+            .map_err(|e| AppError::OllamaUnavailable(e.to_string()))?;
 
         if !response.status().is_success() {
-            return Err(format!("Ollama API error: {}", response.status()));
+            return Err(status_error(response.status(), model));
         }
 
-        let mut stream = response.bytes_stream();
+        let mut lines = NdjsonReader::new(response.bytes_stream());
         let mut full_response = String::new();
+        let mut timing = StreamTiming::new();
+        let mut coalescer = TokenCoalescer::new(*self.coalescing.read());
+        let mut finished = false;
 
-        while let Some(chunk_result) = stream.next().await {
-            match chunk_result {
-                Ok(bytes) => {
-                    // Parse NDJSON line
-                    let text = String::from_utf8_lossy(&bytes);
-                    for line in text.lines() {
-                        if line.is_empty() {
-                            continue;
-                        }
-
-                        match serde_json::from_str::<OllamaStreamResponse>(line) {
-                            Ok(chunk) => {
-                                full_response.push_str(&chunk.response);
-
-                                // Emit chunk to frontend
-                                let stream_chunk = StreamChunk {
-                                    id: request_id.to_string(),
-                                    token: chunk.response,
-                                    done: chunk.done,
-                                    model: Some(chunk.model),
-                                    total_tokens: chunk.eval_count,
-                                };
-
-                                let _ = window.emit("ollama-stream-chunk", &stream_chunk);
-
-                                if chunk.done {
-                                    break;
-                                }
-                            }
-                            Err(e) => {
-                                tracing::warn!("Failed to parse chunk: {} - {}", line, e);
-                            }
-                        }
-                    }
+        while let Some(line) = lines.next_line().await {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    emit_cancelled(target, request_id, &mut coalescer, &timing);
+                    return Err(e);
+                }
+            };
+
+            let chunk = match parse_line::<OllamaStreamResponse>(&line) {
+                Ok(chunk) => chunk,
+                Err(AppError::Stream(error)) => {
+                    emit_cancelled(target, request_id, &mut coalescer, &timing);
+                    return Err(AppError::Stream(error));
                 }
                 Err(e) => {
-                    return Err(format!("Stream error: {}", e));
+                    tracing::warn!("Failed to parse chunk: {} - {}", line, e);
+                    continue;
                 }
+            };
+
+            full_response.push_str(&chunk.response);
+            timing.record(&chunk.response);
+
+            // The final chunk carries whatever is still buffered
+            let Some(token) = coalescer.next_batch(&chunk.response, chunk.done) else {
+                continue;
+            };
+
+            // Emit chunk to frontend
+            let stream_chunk = StreamChunk {
+                id: request_id.to_string(),
+                token,
+                done: chunk.done,
+                model: Some(chunk.model),
+                total_tokens: chunk.eval_count,
+                elapsed_ms: timing.elapsed_ms(),
+                tokens_per_sec: timing.tokens_per_sec(),
+                finish_reason: chunk
+                    .done
+                    .then(|| FinishReason::from_done_reason(chunk.done_reason.as_deref())),
+            };
+
+            target.emit_chunk(&stream_chunk);
+
+            if chunk.done {
+                finished = true;
+                break;
             }
         }
 
+        if !finished {
+            emit_cancelled(target, request_id, &mut coalescer, &timing);
+        }
+
         Ok(full_response)
     }
 
     /// Chat completion with streaming
+    ///
+    /// With `assistant_prefix`, the reply is forced to start with that text:
+    /// it is sent as a trailing assistant message, which Ollama continues
+    /// rather than answering anew. The prefix is part of the streamed and
+    /// returned output, but Ollama matches stop sequences and EOS against
+    /// generated text only, so a prefix cannot end the reply by itself.
     pub async fn chat_stream(
         &self,
-        window: &Window,
+        target: &StreamTarget,
         request_id: &str,
         model: &str,
-        messages: Vec<ChatMessage>,
-    ) -> Result<String, String> {
+        mut messages: Vec<ChatMessage>,
+        assistant_prefix: Option<&str>,
+    ) -> Result<String, AppError> {
         let url = format!("{}/api/chat", self.base_url);
+        let prefix = assistant_prefix.unwrap_or_default();
+        if !prefix.is_empty() {
+            messages.push(ChatMessage {
+                role: "assistant".to_string(),
+                content: prefix.to_string(),
+            });
+        }
 
         let request = OllamaChatRequest {
             model: model.to_string(),
@@ -142,65 +352,85 @@ impl OllamaClient {
             .json(&request)
             .send()
             .await
-            .map_err(|e| format!("Failed to connect to Ollama: {}", e))?;
+            .map_err(|e| AppError::OllamaUnavailable(e.to_string()))?;
 
         if !response.status().is_success() {
-            return Err(format!("Ollama API error: {}", response.status()));
+            return Err(status_error(response.status(), model));
         }
 
-        let mut stream = response.bytes_stream();
-        let mut full_response = String::new();
+        let mut lines = NdjsonReader::new(response.bytes_stream());
+        let mut full_response = prefix.to_string();
+        let mut timing = StreamTiming::new();
+        let mut coalescer = TokenCoalescer::new(*self.coalescing.read());
+        // Not generated, so kept out of the timing; goes out with the first batch
+        coalescer.seed(prefix);
+        let mut finished = false;
+
+        while let Some(line) = lines.next_line().await {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    emit_cancelled(target, request_id, &mut coalescer, &timing);
+                    return Err(e);
+                }
+            };
 
-        while let Some(chunk_result) = stream.next().await {
-            match chunk_result {
-                Ok(bytes) => {
-                    let text = String::from_utf8_lossy(&bytes);
-                    for line in text.lines() {
-                        if line.is_empty() {
-                            continue;
-                        }
-
-                        match serde_json::from_str::<OllamaChatStreamResponse>(line) {
-                            Ok(chunk) => {
-                                let token = chunk
-                                    .message
-                                    .as_ref()
-                                    .map(|m| m.content.clone())
-                                    .unwrap_or_default();
-
-                                full_response.push_str(&token);
-
-                                let stream_chunk = StreamChunk {
-                                    id: request_id.to_string(),
-                                    token,
-                                    done: chunk.done,
-                                    model: Some(chunk.model),
-                                    total_tokens: chunk.eval_count,
-                                };
-
-                                let _ = window.emit("ollama-stream-chunk", &stream_chunk);
-
-                                if chunk.done {
-                                    break;
-                                }
-                            }
-                            Err(e) => {
-                                tracing::warn!("Failed to parse chat chunk: {} - {}", line, e);
-                            }
-                        }
-                    }
+            let chunk = match parse_line::<OllamaChatStreamResponse>(&line) {
+                Ok(chunk) => chunk,
+                Err(AppError::Stream(error)) => {
+                    emit_cancelled(target, request_id, &mut coalescer, &timing);
+                    return Err(AppError::Stream(error));
                 }
                 Err(e) => {
-                    return Err(format!("Stream error: {}", e));
+                    tracing::warn!("Failed to parse chat chunk: {} - {}", line, e);
+                    continue;
                 }
+            };
+
+            let token = chunk
+                .message
+                .as_ref()
+                .map(|m| m.content.clone())
+                .unwrap_or_default();
+
+            full_response.push_str(&token);
+            timing.record(&token);
+
+            // The final chunk carries whatever is still buffered
+            let Some(token) = coalescer.next_batch(&token, chunk.done) else {
+                continue;
+            };
+
+            let stream_chunk = StreamChunk {
+                id: request_id.to_string(),
+                token,
+                done: chunk.done,
+                model: Some(chunk.model),
+                total_tokens: chunk.eval_count,
+                elapsed_ms: timing.elapsed_ms(),
+                tokens_per_sec: timing.tokens_per_sec(),
+                finish_reason: chunk
+                    .done
+                    .then(|| FinishReason::from_done_reason(chunk.done_reason.as_deref())),
+            };
+
+            target.emit_chunk(&stream_chunk);
+
+            if chunk.done {
+                finished = true;
+                break;
             }
         }
 
+        if !finished {
+            emit_cancelled(target, request_id, &mut coalescer, &timing);
+        }
+
         Ok(full_response)
     }
 
     /// Check if Ollama is running
-    pub async fn health_check(&self) -> Result<bool, String> {
+    pub async fn health_check(&self) -> Result<bool, AppError> {
         let url = format!("{}/api/tags", self.base_url);
 
         match self.client.get(&url).send().await {
@@ -215,7 +445,58 @@ impl OllamaClient {
         model: &str,
         prompt: &str,
         options: Option<GenerateOptions>,
-    ) -> Result<String, String> {
+    ) -> Result<String, AppError> {
+        if let Some(opts) = &options {
+            opts.validate()?;
+        }
+
+        let cache_ttl = options
+            .as_ref()
+            .and_then(|o| o.cache_ttl_secs)
+            .filter(|secs| *secs > 0);
+        let cache_key = response_cache::cache_key(model, prompt, options.as_ref());
+
+        if cache_ttl.is_some() {
+            if let Some(cached) = response_cache::get(cache_key) {
+                return Ok(cached);
+            }
+        }
+
+        let response = self.send_generate_sync(model, prompt, options, None).await?;
+
+        if let Some(ttl) = cache_ttl {
+            response_cache::insert(
+                cache_key,
+                response.clone(),
+                std::time::Duration::from_secs(ttl),
+            );
+        }
+
+        Ok(response)
+    }
+
+    /// Generate with Ollama's JSON mode and parse the reply. With a schema
+    /// the output is constrained to it, otherwise to any valid JSON.
+    pub async fn generate_sync_json(
+        &self,
+        model: &str,
+        prompt: &str,
+        schema: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, AppError> {
+        let format = schema.unwrap_or_else(|| serde_json::Value::String("json".to_string()));
+        let response = self.send_generate_sync(model, prompt, None, Some(format)).await?;
+
+        serde_json::from_str(&response)
+            .map_err(|e| AppError::InvalidResponse(format!("Model did not return valid JSON: {}", e)))
+    }
+
+    async fn send_generate_sync(
+        &self,
+        model: &str,
+        prompt: &str,
+        options: Option<GenerateOptions>,
+        format: Option<serde_json::Value>,
+    ) -> Result<String, AppError> {
         let url = format!("{}/api/generate", self.base_url);
 
         let request = OllamaRequestSync {
@@ -223,6 +504,7 @@ impl OllamaClient {
             prompt: prompt.to_string(),
             stream: false,
             options,
+            format,
         };
 
         let response = self
@@ -231,23 +513,199 @@ impl OllamaClient {
             .json(&request)
             .send()
             .await
-            .map_err(|e| format!("Failed to connect to Ollama: {}", e))?;
+            .map_err(|e| AppError::OllamaUnavailable(e.to_string()))?;
 
         if !response.status().is_success() {
-            return Err(format!("Ollama API error: {}", response.status()));
+            return Err(status_error(response.status(), model));
         }
 
         let result: OllamaSyncResponse = response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse response: {}", e))?;
+            .map_err(|e| AppError::InvalidResponse(e.to_string()))?;
 
         Ok(result.response)
     }
 }
 
+/// Close out a stream that ended without Ollama's final chunk, so the
+/// frontend still gets its buffered text and a `done` event
+fn emit_cancelled(target: &StreamTarget, request_id: &str, coalescer: &mut TokenCoalescer, timing: &StreamTiming) {
+    target.emit_chunk(&cancelled_chunk(request_id, coalescer, timing));
+}
+
+/// The `done` chunk for a cut-short stream, carrying everything still buffered
+fn cancelled_chunk(request_id: &str, coalescer: &mut TokenCoalescer, timing: &StreamTiming) -> StreamChunk {
+    StreamChunk {
+        id: request_id.to_string(),
+        token: coalescer.take(),
+        done: true,
+        model: None,
+        total_tokens: None,
+        elapsed_ms: timing.elapsed_ms(),
+        tokens_per_sec: timing.tokens_per_sec(),
+        finish_reason: Some(FinishReason::Cancelled),
+    }
+}
+
+/// Map a non-success HTTP status to an error; Ollama answers 404 for unknown models
+fn status_error(status: reqwest::StatusCode, model: &str) -> AppError {
+    if status == reqwest::StatusCode::NOT_FOUND {
+        AppError::ModelNotFound(model.to_string())
+    } else {
+        AppError::OllamaApi(status.to_string())
+    }
+}
+
 impl Default for OllamaClient {
     fn default() -> Self {
         Self::new(None)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coalescer_batches_by_token_count() {
+        let mut coalescer = TokenCoalescer::new(StreamCoalescing {
+            flush_interval_ms: 60_000,
+            max_tokens: 3,
+        });
+
+        assert_eq!(coalescer.push("a"), None);
+        assert_eq!(coalescer.push(""), None);
+        assert_eq!(coalescer.push("b"), None);
+        assert_eq!(coalescer.push("c").as_deref(), Some("abc"));
+        assert_eq!(coalescer.push("d"), None);
+        assert_eq!(coalescer.take(), "d");
+        assert_eq!(coalescer.take(), "");
+    }
+
+    #[test]
+    fn test_finish_reason_mapping() {
+        assert_eq!(FinishReason::from_done_reason(Some("length")), FinishReason::Length);
+        assert_eq!(FinishReason::from_done_reason(Some("stop")), FinishReason::EndOfGeneration);
+        assert_eq!(FinishReason::from_done_reason(Some("unload")), FinishReason::Unknown);
+        assert_eq!(FinishReason::from_done_reason(None), FinishReason::Unknown);
+        assert_eq!(serde_json::to_value(FinishReason::Unknown).unwrap(), "unknown");
+    }
+
+    #[test]
+    fn test_model_name_validation() {
+        assert!(validate_model_name("llama3.2:3b").is_ok());
+        assert!(validate_model_name("my-model_v1.0:latest").is_ok());
+        assert!(validate_model_name("").is_err());
+        assert!(validate_model_name(&"a".repeat(101)).is_err());
+        assert!(validate_model_name("user/model").is_err());
+        assert!(validate_model_name("model name").is_err());
+    }
+
+    #[test]
+    fn test_generate_options_validation() {
+        let options = |temperature, num_predict| GenerateOptions {
+            temperature,
+            num_predict,
+            ..Default::default()
+        };
+
+        assert!(options(Some(0.7), Some(512)).validate().is_ok());
+        assert!(options(Some(2.0), Some(32768)).validate().is_ok());
+        assert!(options(Some(2.5), None).validate().is_err());
+        assert!(options(None, Some(40000)).validate().is_err());
+    }
+
+    #[test]
+    fn test_generate_options_boundaries() {
+        let valid = |options: GenerateOptions| options.validate().is_ok();
+
+        assert!(valid(GenerateOptions { temperature: Some(0.0), ..Default::default() }));
+        assert!(!valid(GenerateOptions { temperature: Some(-0.1), ..Default::default() }));
+        assert!(valid(GenerateOptions { top_p: Some(0.0), ..Default::default() }));
+        assert!(valid(GenerateOptions { top_p: Some(1.0), ..Default::default() }));
+        assert!(!valid(GenerateOptions { top_p: Some(1.01), ..Default::default() }));
+        assert!(valid(GenerateOptions { top_k: Some(0), ..Default::default() }));
+        assert!(!valid(GenerateOptions { top_k: Some(-1), ..Default::default() }));
+        assert!(valid(GenerateOptions { num_predict: Some(-2), ..Default::default() }));
+        assert!(!valid(GenerateOptions { num_predict: Some(-3), ..Default::default() }));
+        assert!(valid(GenerateOptions { num_predict: Some(32768), ..Default::default() }));
+        assert!(!valid(GenerateOptions { num_predict: Some(32769), ..Default::default() }));
+    }
+
+    #[test]
+    fn test_coalescer_flushes_on_done() {
+        let mut coalescer = TokenCoalescer::new(StreamCoalescing {
+            flush_interval_ms: 50,
+            max_tokens: 16,
+        });
+
+        assert_eq!(coalescer.next_batch("Hello", false), None);
+        assert_eq!(coalescer.next_batch(" world", false), None);
+        // The interval has passed, so `push` releases the batch on the done chunk
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(coalescer.next_batch("", true).as_deref(), Some("Hello world"));
+
+        assert_eq!(coalescer.next_batch("!", true).as_deref(), Some("!"));
+        assert_eq!(coalescer.next_batch("", true).as_deref(), Some(""));
+    }
+
+    #[test]
+    fn test_coalescer_single_token_mode() {
+        let mut coalescer = TokenCoalescer::new(StreamCoalescing {
+            flush_interval_ms: 60_000,
+            max_tokens: 0,
+        });
+        assert_eq!(coalescer.push("a").as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn test_coalescer_seed_leads_first_batch() {
+        let mut coalescer = TokenCoalescer::new(StreamCoalescing {
+            flush_interval_ms: 60_000,
+            max_tokens: 2,
+        });
+        coalescer.seed("```json\n");
+
+        // The seed is not a token, so it does not bring the flush forward
+        assert_eq!(coalescer.push("{"), None);
+        assert_eq!(coalescer.push("}").as_deref(), Some("```json\n{}"));
+    }
+
+    #[test]
+    fn test_transport_error_flushes_buffered_text() {
+        let body: Vec<Result<&[u8], String>> = vec![
+            Ok(&b"{\"model\":\"m\",\"response\":\"Hel\",\"done\":false}\n"[..]),
+            Ok(&b"{\"model\":\"m\",\"response\":\"lo\",\"done\":false}\n"[..]),
+            Err("connection reset".to_string()),
+        ];
+        let mut lines = NdjsonReader::new(futures_util::stream::iter(body));
+        let mut coalescer = TokenCoalescer::new(StreamCoalescing {
+            flush_interval_ms: 60_000,
+            max_tokens: 16,
+        });
+        let timing = StreamTiming::new();
+
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let chunk = runtime.block_on(async {
+            while let Some(line) = lines.next_line().await {
+                match line {
+                    Ok(line) => {
+                        let chunk: OllamaStreamResponse = parse_line(&line).unwrap();
+                        assert_eq!(coalescer.next_batch(&chunk.response, chunk.done), None);
+                    }
+                    Err(e) => {
+                        assert!(matches!(e, AppError::Stream(_)));
+                        return Some(cancelled_chunk("req", &mut coalescer, &timing));
+                    }
+                }
+            }
+            None
+        });
+
+        let chunk = chunk.expect("transport error should end the stream");
+        assert_eq!(chunk.token, "Hello");
+        assert!(chunk.done);
+        assert_eq!(chunk.finish_reason, Some(FinishReason::Cancelled));
+    }
+}
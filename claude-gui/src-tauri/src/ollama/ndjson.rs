@@ -0,0 +1,148 @@
+//! Reader for Ollama's newline-delimited JSON streams
+//!
+//! HTTP chunks don't line up with JSON objects: one chunk can carry several
+//! lines, and a line (or a multi-byte character) can be split across two.
+//! Bytes are buffered until a full line arrives, and only then parsed.
+
+use futures_util::{Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+use crate::error::AppError;
+
+/// Accumulates raw bytes and hands out complete lines
+#[derive(Debug, Default)]
+pub struct NdjsonBuffer {
+    pending: Vec<u8>,
+}
+
+impl NdjsonBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a chunk, returning every line it completed. Blank lines are dropped.
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(bytes);
+
+        let Some(last_newline) = self.pending.iter().rposition(|&b| b == b'\n') else {
+            return Vec::new();
+        };
+
+        let complete: Vec<u8> = self.pending.drain(..=last_newline).collect();
+        complete
+            .split(|&b| b == b'\n')
+            .map(|line| String::from_utf8_lossy(line).trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+
+    /// The unterminated last line, if any, once the stream has ended
+    pub fn finish(&mut self) -> Option<String> {
+        let rest = std::mem::take(&mut self.pending);
+        let line = String::from_utf8_lossy(&rest).trim().to_string();
+        (!line.is_empty()).then_some(line)
+    }
+}
+
+/// Pulls complete lines out of a byte stream such as `Response::bytes_stream`
+pub struct NdjsonReader<S> {
+    stream: S,
+    buffer: NdjsonBuffer,
+    ready: VecDeque<String>,
+    ended: bool,
+}
+
+impl<S, B, E> NdjsonReader<S>
+where
+    S: Stream<Item = Result<B, E>> + Unpin,
+    B: AsRef<[u8]>,
+    E: Display,
+{
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            buffer: NdjsonBuffer::new(),
+            ready: VecDeque::new(),
+            ended: false,
+        }
+    }
+
+    /// Next complete line, or `None` once the stream is exhausted
+    pub async fn next_line(&mut self) -> Option<Result<String, AppError>> {
+        loop {
+            if let Some(line) = self.ready.pop_front() {
+                return Some(Ok(line));
+            }
+            if self.ended {
+                return None;
+            }
+
+            match self.stream.next().await {
+                Some(Ok(bytes)) => self.ready.extend(self.buffer.push(bytes.as_ref())),
+                Some(Err(e)) => return Some(Err(AppError::Stream(e.to_string()))),
+                None => {
+                    self.ended = true;
+                    self.ready.extend(self.buffer.finish());
+                }
+            }
+        }
+    }
+}
+
+/// Parse one line. Ollama reports mid-stream failures as `{"error": "..."}`,
+/// which comes back as [`AppError::Stream`].
+pub fn parse_line<T: DeserializeOwned>(line: &str) -> Result<T, AppError> {
+    let value: serde_json::Value =
+        serde_json::from_str(line).map_err(|e| AppError::InvalidResponse(e.to_string()))?;
+
+    if let Some(error) = value.get("error").and_then(|e| e.as_str()) {
+        return Err(AppError::Stream(error.to_string()));
+    }
+
+    serde_json::from_value(value).map_err(|e| AppError::InvalidResponse(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ollama::types::OllamaStreamResponse;
+
+    #[test]
+    fn test_objects_split_across_chunks() {
+        let mut buffer = NdjsonBuffer::new();
+
+        let first = buffer.push(b"{\"model\":\"m\",\"response\":\"Hel\",\"done\":false}\n{\"model\":\"m\",\"resp");
+        assert_eq!(first.len(), 1);
+
+        // "ł" is two bytes; split it between chunks too
+        let second = buffer.push(b"onse\":\"lo \xC5");
+        assert!(second.is_empty());
+        let third = buffer.push(b"\x82\",\"done\":false}\n{\"model\":\"m\",\"done\":true,\"done_reason\":\"stop\"}");
+        assert_eq!(third.len(), 1);
+        let trailing = buffer.finish().expect("unterminated final object");
+
+        let chunks: Vec<OllamaStreamResponse> = first
+            .iter()
+            .chain(third.iter())
+            .chain(std::iter::once(&trailing))
+            .map(|line| parse_line(line).unwrap())
+            .collect();
+
+        let text: String = chunks.iter().map(|c| c.response.as_str()).collect();
+        assert_eq!(text, "Hello ł");
+        assert!(chunks.last().unwrap().done);
+        assert!(buffer.finish().is_none());
+    }
+
+    #[test]
+    fn test_error_object() {
+        let err = parse_line::<OllamaStreamResponse>("{\"error\":\"model not found\"}").unwrap_err();
+        assert!(matches!(err, AppError::Stream(msg) if msg == "model not found"));
+        assert!(matches!(
+            parse_line::<OllamaStreamResponse>("{not json"),
+            Err(AppError::InvalidResponse(_))
+        ));
+    }
+}
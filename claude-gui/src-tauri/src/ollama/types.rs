@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::error::AppError;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OllamaRequest {
     pub model: String,
@@ -33,6 +35,9 @@ pub struct OllamaStreamResponse {
     pub response: String,
     #[serde(default)]
     pub done: bool,
+    /// Why generation ended; only set on the final chunk
+    #[serde(default)]
+    pub done_reason: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub context: Option<Vec<i64>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -48,6 +53,9 @@ pub struct OllamaChatStreamResponse {
     pub message: Option<ChatMessage>,
     #[serde(default)]
     pub done: bool,
+    /// Why generation ended; only set on the final chunk
+    #[serde(default)]
+    pub done_reason: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub total_duration: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -57,6 +65,7 @@ pub struct OllamaChatStreamResponse {
 /// Event sent to frontend during streaming
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StreamChunk {
+    /// Stream id; chunks of every stream share one event name
     pub id: String,
     pub token: String,
     pub done: bool,
@@ -64,6 +73,58 @@ pub struct StreamChunk {
     pub model: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub total_tokens: Option<u64>,
+    /// Milliseconds since the request was sent
+    pub elapsed_ms: u64,
+    /// Running decode throughput, measured from the first streamed token
+    pub tokens_per_sec: f64,
+    /// Set on the final (`done`) chunk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finish_reason: Option<FinishReason>,
+}
+
+/// Why a generation ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FinishReason {
+    /// Hit `num_predict`
+    Length,
+    /// The stream closed before Ollama sent its final chunk
+    Cancelled,
+    /// The model emitted its end-of-sequence token
+    EndOfGeneration,
+    /// Ollama gave no reason, or one we don't recognise (e.g. `"unload"`)
+    Unknown,
+}
+
+impl FinishReason {
+    /// Map Ollama's `done_reason`. The streaming commands send no stop
+    /// sequences, so `"stop"` can only mean the end-of-sequence token.
+    pub fn from_done_reason(done_reason: Option<&str>) -> Self {
+        match done_reason {
+            Some("length") => FinishReason::Length,
+            Some("stop") => FinishReason::EndOfGeneration,
+            _ => FinishReason::Unknown,
+        }
+    }
+}
+
+/// How streamed tokens are batched into `ollama-stream-chunk` events.
+/// A chunk is emitted once `max_tokens` have accumulated or
+/// `flush_interval_ms` has passed since the last one; `max_tokens: 1`
+/// emits every token.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct StreamCoalescing {
+    pub flush_interval_ms: u64,
+    pub max_tokens: u32,
+}
+
+impl Default for StreamCoalescing {
+    fn default() -> Self {
+        Self {
+            flush_interval_ms: 50,
+            max_tokens: 16,
+        }
+    }
 }
 
 /// Models list response
@@ -81,17 +142,102 @@ pub struct OllamaModel {
     pub size: Option<u64>,
 }
 
-/// Options for generate request
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaRunningModelsResponse {
+    #[serde(default)]
+    pub models: Vec<OllamaRunningModel>,
+}
+
+/// A model currently loaded in Ollama's memory (`/api/ps`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaRunningModel {
+    pub name: String,
+    pub model: String,
+    #[serde(default)]
+    pub size_vram: u64,
+    #[serde(default)]
+    pub expires_at: String,
+}
+
+const MAX_MODEL_NAME_LEN: usize = 100;
+
+/// Model names as accepted by copy: `[a-zA-Z0-9_:.-]+`, at most 100 characters
+pub fn validate_model_name(name: &str) -> Result<(), AppError> {
+    if name.is_empty() || name.len() > MAX_MODEL_NAME_LEN {
+        return Err(AppError::InvalidInput(format!(
+            "Model name must be 1-{} characters",
+            MAX_MODEL_NAME_LEN
+        )));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | ':' | '.' | '-')) {
+        return Err(AppError::InvalidInput(format!("Invalid model name: '{}'", name)));
+    }
+    Ok(())
+}
+
+/// Options for generate request
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GenerateOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub num_predict: Option<u32>,
+    pub num_predict: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub top_p: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub top_k: Option<u32>,
+    pub top_k: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_ctx: Option<u32>,
+    /// Reuse an identical earlier response for this many seconds (client-side only)
+    #[serde(default, skip_serializing)]
+    pub cache_ttl_secs: Option<u64>,
+}
+
+impl GenerateOptions {
+    pub const MAX_TEMPERATURE: f32 = 2.0;
+    pub const MAX_NUM_PREDICT: i32 = 32768;
+
+    /// Reject values Ollama would silently clamp or choke on
+    pub fn validate(&self) -> Result<(), AppError> {
+        if let Some(temperature) = self.temperature {
+            if !(0.0..=Self::MAX_TEMPERATURE).contains(&temperature) {
+                return Err(AppError::InvalidInput(format!(
+                    "temperature must be between 0 and {}, got {}",
+                    Self::MAX_TEMPERATURE,
+                    temperature
+                )));
+            }
+        }
+
+        // Ollama uses -1 for "until done" and -2 for "fill the context"
+        if let Some(num_predict) = self.num_predict {
+            if !(-2..=Self::MAX_NUM_PREDICT).contains(&num_predict) {
+                return Err(AppError::InvalidInput(format!(
+                    "num_predict must be between -2 and {}, got {}",
+                    Self::MAX_NUM_PREDICT,
+                    num_predict
+                )));
+            }
+        }
+
+        if let Some(top_p) = self.top_p {
+            if !(0.0..=1.0).contains(&top_p) {
+                return Err(AppError::InvalidInput(format!("top_p must be between 0 and 1, got {}", top_p)));
+            }
+        }
+
+        if let Some(top_k) = self.top_k {
+            if top_k < 0 {
+                return Err(AppError::InvalidInput(format!("top_k must not be negative, got {}", top_k)));
+            }
+        }
+
+        Ok(())
+    }
 }
 
 /// Sync request (no streaming)
@@ -103,6 +249,9 @@ pub struct OllamaRequestSync {
     pub stream: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub options: Option<GenerateOptions>,
+    /// `"json"` or a JSON schema for structured output
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<serde_json::Value>,
 }
 
 /// Sync response (complete, no streaming)
@@ -1,9 +1,13 @@
-use tauri::{command, State, Window};
+use tauri::{command, AppHandle, Emitter, State, Window};
 use tokio::sync::RwLock;
 use std::sync::Arc;
 
-use crate::ollama::client::OllamaClient;
-use crate::ollama::types::{ChatMessage, GenerateOptions, OllamaModel};
+use crate::error::AppError;
+use crate::ollama::client::{OllamaClient, StreamTarget};
+use crate::ollama::types::{
+    validate_model_name, ChatMessage, GenerateOptions, OllamaModel, OllamaRunningModel, StreamCoalescing,
+};
+use crate::security::{sanitize_prompt_injection, SecurityWarning};
 
 pub struct OllamaState {
     pub client: Arc<RwLock<OllamaClient>>,
@@ -25,47 +29,127 @@ impl Default for OllamaState {
 
 /// List available Ollama models
 #[command]
-pub async fn ollama_list_models(state: State<'_, OllamaState>) -> Result<Vec<OllamaModel>, String> {
+pub async fn ollama_list_models(state: State<'_, OllamaState>) -> Result<Vec<OllamaModel>, AppError> {
     let client = state.client.read().await;
     client.list_models().await
 }
 
+/// Copy a model under a new name; emits `ollama-models-changed` on success
+#[command]
+pub async fn ollama_copy_model(
+    state: State<'_, OllamaState>,
+    window: Window,
+    source: String,
+    destination: String,
+) -> Result<(), AppError> {
+    validate_model_name(&source)?;
+    validate_model_name(&destination)?;
+
+    let client = state.client.read().await;
+    client.copy_model(&source, &destination).await?;
+
+    let _ = window.emit("ollama-models-changed", ());
+    Ok(())
+}
+
+/// Models currently loaded in Ollama's memory
+#[command]
+pub async fn ollama_running_models(state: State<'_, OllamaState>) -> Result<Vec<OllamaRunningModel>, AppError> {
+    let client = state.client.read().await;
+    client.running_models().await
+}
+
 /// Check if Ollama is running
 #[command]
-pub async fn ollama_health_check(state: State<'_, OllamaState>) -> Result<bool, String> {
+pub async fn ollama_health_check(state: State<'_, OllamaState>) -> Result<bool, AppError> {
     let client = state.client.read().await;
     client.health_check().await
 }
 
+/// How a streaming command delivers its chunks
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct StreamOptions {
+    /// Id carried by every chunk; generated when not given
+    pub stream_id: Option<String>,
+    /// Emit only to the calling window instead of every window
+    #[serde(default)]
+    pub window_only: bool,
+}
+
+impl StreamOptions {
+    fn into_target(self, app: AppHandle, window: Window) -> (String, StreamTarget) {
+        let stream_id = self.stream_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let target = if self.window_only {
+            StreamTarget::Window(window)
+        } else {
+            StreamTarget::All(app)
+        };
+        (stream_id, target)
+    }
+}
+
 /// Generate completion with streaming
+///
+/// Chunks go to every window as `ollama-stream-chunk`, tagged with the
+/// stream id, so any window can follow along by filtering on it.
 #[command]
 pub async fn ollama_generate(
     state: State<'_, OllamaState>,
+    app: AppHandle,
     window: Window,
     model: String,
     prompt: String,
     system: Option<String>,
-) -> Result<String, String> {
-    let request_id = uuid::Uuid::new_v4().to_string();
+    stream: Option<StreamOptions>,
+) -> Result<String, AppError> {
+    let (request_id, target) = stream.unwrap_or_default().into_target(app, window);
     let client = state.client.read().await;
 
     client
-        .generate_stream(&window, &request_id, &model, &prompt, system)
+        .generate_stream(&target, &request_id, &model, &prompt, system)
         .await
 }
 
-/// Chat completion with streaming
+/// Chat completion with streaming; see [`ollama_generate`] for how chunks are delivered
+///
+/// `assistant_prefix` forces the reply to begin with the given text, e.g.
+/// "```json" to steer the model into a code block.
 #[command]
 pub async fn ollama_chat(
     state: State<'_, OllamaState>,
+    app: AppHandle,
     window: Window,
     model: String,
-    messages: Vec<ChatMessage>,
-) -> Result<String, String> {
-    let request_id = uuid::Uuid::new_v4().to_string();
+    mut messages: Vec<ChatMessage>,
+    stream: Option<StreamOptions>,
+    assistant_prefix: Option<String>,
+) -> Result<String, AppError> {
+    // Screen user-supplied turns before they reach the model
+    let mut patterns: Vec<String> = Vec::new();
+    for message in messages.iter_mut().filter(|m| m.role == "user") {
+        let (cleaned, detected) = sanitize_prompt_injection(&message.content);
+        message.content = cleaned;
+        for pattern in detected {
+            if !patterns.contains(&pattern) {
+                patterns.push(pattern);
+            }
+        }
+    }
+
+    if !patterns.is_empty() {
+        tracing::warn!("Prompt injection patterns in ollama_chat: {:?}", patterns);
+        let _ = window.emit("security-warning", &SecurityWarning {
+            source: "ollama_chat".to_string(),
+            patterns,
+        });
+    }
+
+    let (request_id, target) = stream.unwrap_or_default().into_target(app, window);
     let client = state.client.read().await;
 
-    client.chat_stream(&window, &request_id, &model, messages).await
+    client
+        .chat_stream(&target, &request_id, &model, messages, assistant_prefix.as_deref())
+        .await
 }
 
 /// Generate completion synchronously (no streaming, for AI metadata tasks)
@@ -75,57 +159,107 @@ pub async fn ollama_generate_sync(
     model: String,
     prompt: String,
     options: Option<GenerateOptions>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let client = state.client.read().await;
     client.generate_sync(&model, &prompt, options).await
 }
 
+/// Generate a JSON response, optionally constrained to a JSON schema
+#[command]
+pub async fn ollama_generate_sync_json(
+    state: State<'_, OllamaState>,
+    model: String,
+    prompt: String,
+    json_schema: Option<String>,
+) -> Result<serde_json::Value, AppError> {
+    let schema = json_schema
+        .map(|schema| serde_json::from_str(&schema))
+        .transpose()
+        .map_err(|e| AppError::InvalidInput(format!("Invalid JSON schema: {}", e)))?;
+
+    let client = state.client.read().await;
+    client.generate_sync_json(&model, &prompt, schema).await
+}
+
+/// Default number of prompts sent to Ollama at once in a batch
+const DEFAULT_BATCH_CONCURRENCY: usize = 4;
+
 /// Batch generate completions - wykorzystaj wszystkie rdzenie!
-/// Przetwarza wiele promptów równolegle dla maksymalnej wydajności.
+/// Przetwarza wiele promptów równolegle, ale najwyżej `max_concurrent`
+/// naraz, żeby nie zalać pojedynczej instancji Ollamy.
+/// Emits `batch-progress` as each prompt finishes.
 #[command]
 pub async fn ollama_batch_generate(
+    window: Window,
     state: State<'_, OllamaState>,
     model: String,
     prompts: Vec<String>,
     options: Option<GenerateOptions>,
-) -> Result<Vec<BatchResult>, String> {
-    use futures_util::future::join_all;
+    max_concurrent: Option<usize>,
+) -> Result<Vec<BatchResult>, AppError> {
+    use futures_util::stream::{FuturesUnordered, StreamExt};
+    use tokio::sync::Semaphore;
 
+    // Reject bad options once instead of failing every prompt the same way
+    if let Some(opts) = &options {
+        opts.validate()?;
+    }
+
+    let max_concurrent = max_concurrent.unwrap_or(DEFAULT_BATCH_CONCURRENCY).max(1);
+    let semaphore = Semaphore::new(max_concurrent);
     let client = state.client.read().await;
-    let opts = options.clone();
+    let total = prompts.len();
 
-    // Uruchom wszystkie requesty równolegle
-    let futures: Vec<_> = prompts
-        .iter()
+    let mut pending: FuturesUnordered<_> = prompts
+        .into_iter()
         .enumerate()
         .map(|(idx, prompt)| {
-            let model = model.clone();
-            let prompt = prompt.clone();
-            let opts = opts.clone();
+            let model = &model;
+            let opts = options.clone();
             let client_ref = &client;
+            let semaphore = &semaphore;
 
             async move {
+                let queued_at = std::time::Instant::now();
+                // The semaphore is never closed, so acquire cannot fail
+                let _permit = semaphore.acquire().await.ok();
+                let queued_ms = queued_at.elapsed().as_millis() as u64;
+
                 let start = std::time::Instant::now();
-                let result = client_ref.generate_sync(&model, &prompt, opts).await;
+                let result = client_ref.generate_sync(model, &prompt, opts).await;
                 let duration_ms = start.elapsed().as_millis() as u64;
 
                 let (response, error) = match result {
                     Ok(resp) => (Some(resp), None),
-                    Err(err) => (None, Some(err)),
+                    Err(err) => (None, Some(err.to_string())),
                 };
 
                 BatchResult {
                     index: idx,
-                    prompt: prompt.clone(),
+                    prompt,
                     response,
                     error,
                     duration_ms,
+                    queued_ms,
                 }
             }
         })
         .collect();
 
-    let results = join_all(futures).await;
+    let mut results = Vec::with_capacity(total);
+    while let Some(result) = pending.next().await {
+        let _ = window.emit(
+            "batch-progress",
+            BatchProgress {
+                completed: results.len() + 1,
+                total,
+                result: result.clone(),
+            },
+        );
+        results.push(result);
+    }
+
+    results.sort_by_key(|r| r.index);
     Ok(results)
 }
 
@@ -136,6 +270,29 @@ pub struct BatchResult {
     pub response: Option<String>,
     pub error: Option<String>,
     pub duration_ms: u64,
+    /// Time spent waiting for a concurrency slot
+    pub queued_ms: u64,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct BatchProgress {
+    pub completed: usize,
+    pub total: usize,
+    pub result: BatchResult,
+}
+
+/// Configure how streamed tokens are batched into events
+#[command]
+pub async fn ollama_set_stream_coalescing(
+    state: State<'_, OllamaState>,
+    flush_interval_ms: u64,
+    max_tokens: u32,
+) -> Result<(), AppError> {
+    state.client.read().await.set_stream_coalescing(StreamCoalescing {
+        flush_interval_ms,
+        max_tokens,
+    });
+    Ok(())
 }
 
 /// Get CPU info for performance monitoring
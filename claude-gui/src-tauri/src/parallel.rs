@@ -5,6 +5,7 @@
 #![allow(dead_code)]
 
 use rayon::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use parking_lot::RwLock;
 
@@ -28,6 +29,11 @@ pub struct CpuInfo {
 }
 
 /// Przetwarza elementy równolegle z progress callback
+///
+/// Kolejność wyników zawsze odpowiada kolejności `items` (rayon zachowuje ją
+/// dla `into_par_iter().map().collect()`), niezależnie od tego, które elementy
+/// skończą się pierwsze. Licznik `progress` rośnie natomiast w kolejności
+/// ukończenia, więc jego wartość nie mówi, *które* elementy są gotowe.
 pub fn parallel_process<T, R, F>(
     items: Vec<T>,
     processor: F,
@@ -51,6 +57,26 @@ where
         .collect()
 }
 
+/// Jak `parallel_process`, ale każdy wynik niesie indeks elementu wejściowego.
+/// Dla kodu, który dopasowuje wyniki po pozycji (np. wyniki batch-generate do
+/// promptów) i nie chce polegać na kolejności zwracanej listy.
+pub fn parallel_process_indexed<T, R, F>(
+    items: Vec<T>,
+    processor: F,
+    progress: Option<Arc<RwLock<usize>>>,
+) -> Vec<(usize, R)>
+where
+    T: Send + Sync,
+    R: Send,
+    F: Fn(T) -> R + Send + Sync,
+{
+    parallel_process(
+        items.into_iter().enumerate().collect(),
+        |(index, item)| (index, processor(item)),
+        progress,
+    )
+}
+
 /// Parallel batch processing z limitem
 pub fn parallel_batch<T, R, F>(
     items: Vec<T>,
@@ -68,6 +94,39 @@ where
         .collect()
 }
 
+/// Jak `parallel_batch`, ale pożycza elementy zamiast je klonować i sprawdza
+/// `cancel` przed każdą paczką. Zwraca jeden wpis na paczkę, w kolejności
+/// wejścia: paczka `i` obejmuje `items[i * batch_size..]`, a `None` oznacza
+/// paczkę pominiętą po anulowaniu. Rayon nie przetwarza paczek po kolei,
+/// więc pominięte paczki nie muszą leżeć na końcu.
+///
+/// Batch vs per-item: `parallel_process` wywołuje closure dla każdego
+/// elementu, co wystarcza przy ciężkiej pracy na element. Paczki opłacają się,
+/// gdy praca na element jest tania, a closure ma stały koszt (alokacja bufora,
+/// lock, request), albo gdy trzeba móc przerwać długie zadanie.
+pub fn parallel_batch_cancellable<T, R, F>(
+    items: &[T],
+    batch_size: usize,
+    cancel: &AtomicBool,
+    processor: F,
+) -> Vec<Option<Vec<R>>>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&[T]) -> Vec<R> + Send + Sync,
+{
+    items
+        .par_chunks(batch_size.max(1))
+        .map(|chunk| {
+            if cancel.load(Ordering::Relaxed) {
+                None
+            } else {
+                Some(processor(chunk))
+            }
+        })
+        .collect()
+}
+
 /// Parallel string search (fuzzy matching)
 pub fn parallel_fuzzy_search(
     data: &[String],
@@ -155,6 +214,106 @@ mod tests {
         assert_eq!(results.len(), 5);
     }
 
+    #[test]
+    fn test_output_order_matches_input_order() {
+        use std::time::Duration;
+
+        // Early items take longest, so they finish last
+        let delays: Vec<u64> = vec![40, 5, 30, 0, 20, 10, 35, 1, 15, 25];
+        let progress = Arc::new(RwLock::new(0));
+
+        let results = parallel_process_indexed(
+            delays.clone(),
+            |ms| {
+                std::thread::sleep(Duration::from_millis(ms));
+                ms * 10
+            },
+            Some(progress.clone()),
+        );
+
+        assert_eq!(*progress.read(), delays.len());
+        for (position, (index, value)) in results.iter().enumerate() {
+            assert_eq!(*index, position);
+            assert_eq!(*value, delays[position] * 10);
+        }
+
+        let plain = parallel_process(
+            delays.clone(),
+            |ms| {
+                std::thread::sleep(Duration::from_millis(ms));
+                ms
+            },
+            None,
+        );
+        assert_eq!(plain, delays);
+    }
+
+    #[test]
+    fn test_batch_cancellable_stops_early() {
+        let items: Vec<u32> = (0..1_000).collect();
+        let cancel = AtomicBool::new(false);
+
+        let all = parallel_batch_cancellable(&items, 10, &cancel, |chunk| {
+            chunk.iter().map(|x| x * 2).collect()
+        });
+        assert_eq!(all.len(), 100);
+        let all: Vec<u32> = all.into_iter().flatten().flatten().collect();
+        assert_eq!(all, items.iter().map(|x| x * 2).collect::<Vec<_>>());
+
+        cancel.store(true, Ordering::Relaxed);
+        let none = parallel_batch_cancellable(&items, 10, &cancel, |chunk| chunk.to_vec());
+        assert_eq!(none.len(), 100);
+        assert!(none.iter().all(Option::is_none));
+
+        // Cancelled from inside: every chunk that did run is complete and can be
+        // matched back to its inputs by position
+        let cancel = AtomicBool::new(false);
+        let partial = parallel_batch_cancellable(&items, 10, &cancel, |chunk| {
+            if chunk[0] >= 500 {
+                cancel.store(true, Ordering::Relaxed);
+            }
+            chunk.to_vec()
+        });
+        assert_eq!(partial.len(), 100);
+        for (i, result) in partial.iter().enumerate() {
+            if let Some(result) = result {
+                assert_eq!(result.as_slice(), &items[i * 10..(i + 1) * 10]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_batching_reduces_closure_calls() {
+        use std::sync::atomic::AtomicUsize;
+
+        let items: Vec<u64> = (0..100_000).collect();
+        let cancel = AtomicBool::new(false);
+
+        let per_item_calls = AtomicUsize::new(0);
+        let per_item: Vec<u64> = parallel_process(
+            items.clone(),
+            |x| {
+                per_item_calls.fetch_add(1, Ordering::Relaxed);
+                x * 3
+            },
+            None,
+        );
+
+        let batch_calls = AtomicUsize::new(0);
+        let batched: Vec<u64> = parallel_batch_cancellable(&items, 1_000, &cancel, |chunk| {
+            batch_calls.fetch_add(1, Ordering::Relaxed);
+            chunk.iter().map(|x| x * 3).collect()
+        })
+        .into_iter()
+        .flatten()
+        .flatten()
+        .collect();
+
+        assert_eq!(per_item, batched);
+        assert_eq!(per_item_calls.load(Ordering::Relaxed), 100_000);
+        assert_eq!(batch_calls.load(Ordering::Relaxed), 100);
+    }
+
     #[test]
     fn test_fuzzy_search() {
         let data = vec![
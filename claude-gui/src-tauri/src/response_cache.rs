@@ -0,0 +1,129 @@
+//! Response cache for repeated identical prompts
+//!
+//! Entries are keyed by a hash of the model, prompt and sampling options and
+//! only used when the caller opts in with a TTL.
+
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+
+use crate::ollama::types::GenerateOptions;
+
+const MAX_CACHE_ENTRIES: usize = 500;
+
+pub struct CachedResponse {
+    pub response: String,
+    pub created_at: Instant,
+    pub ttl: Duration,
+}
+
+impl CachedResponse {
+    fn is_expired(&self) -> bool {
+        self.created_at.elapsed() >= self.ttl
+    }
+}
+
+#[derive(Default)]
+pub struct ResponseCache {
+    entries: HashMap<u64, CachedResponse>,
+    hits: u64,
+    misses: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheStats {
+    pub entries: u32,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+lazy_static::lazy_static! {
+    static ref RESPONSE_CACHE: RwLock<ResponseCache> = RwLock::new(ResponseCache::default());
+}
+
+/// Cache key over everything that affects the generated text
+pub fn cache_key(model: &str, prompt: &str, options: Option<&GenerateOptions>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    model.hash(&mut hasher);
+    prompt.hash(&mut hasher);
+    if let Some(opts) = options {
+        opts.temperature.map(f32::to_bits).hash(&mut hasher);
+        opts.num_predict.hash(&mut hasher);
+        opts.top_p.map(f32::to_bits).hash(&mut hasher);
+        opts.top_k.hash(&mut hasher);
+        opts.stop.hash(&mut hasher);
+        opts.seed.hash(&mut hasher);
+        opts.num_ctx.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+pub fn get(key: u64) -> Option<String> {
+    let mut cache = RESPONSE_CACHE.write();
+    let hit = match cache.entries.get(&key) {
+        Some(entry) if !entry.is_expired() => Some(entry.response.clone()),
+        Some(_) => {
+            cache.entries.remove(&key);
+            None
+        }
+        None => None,
+    };
+
+    if hit.is_some() {
+        cache.hits += 1;
+    } else {
+        cache.misses += 1;
+    }
+    hit
+}
+
+pub fn insert(key: u64, response: String, ttl: Duration) {
+    let mut cache = RESPONSE_CACHE.write();
+    cache.entries.retain(|_, entry| !entry.is_expired());
+
+    if cache.entries.len() >= MAX_CACHE_ENTRIES {
+        let oldest = cache
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.created_at)
+            .map(|(key, _)| *key);
+        if let Some(oldest) = oldest {
+            cache.entries.remove(&oldest);
+        }
+    }
+
+    cache.entries.insert(
+        key,
+        CachedResponse {
+            response,
+            created_at: Instant::now(),
+            ttl,
+        },
+    );
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Drop all cached responses, returning how many were removed
+#[tauri::command]
+pub fn clear_response_cache() -> Result<u32, String> {
+    let mut cache = RESPONSE_CACHE.write();
+    let cleared = cache.entries.len() as u32;
+    cache.entries.clear();
+    Ok(cleared)
+}
+
+#[tauri::command]
+pub fn get_cache_stats() -> Result<CacheStats, String> {
+    let cache = RESPONSE_CACHE.read();
+    Ok(CacheStats {
+        entries: cache.entries.values().filter(|e| !e.is_expired()).count() as u32,
+        hits: cache.hits,
+        misses: cache.misses,
+    })
+}
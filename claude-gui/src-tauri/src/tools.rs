@@ -0,0 +1,361 @@
+//! Agent tool registry
+//!
+//! Tools describe capabilities agents may invoke. Each tool names a built-in
+//! `handler` that maps onto an existing command; registering a tool with a
+//! different name or description lets the same handler be offered under
+//! several personas. The registry is persisted to `tools.json`.
+
+use parking_lot::RwLock;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::{agentic, learning, math, memory};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: String,
+    pub input_schema: Value,
+    /// One of [`HANDLERS`]
+    pub handler: String,
+}
+
+/// Built-in handlers a tool can be bound to
+const HANDLERS: &[&str] = &[
+    "execute_command",
+    "get_agent_memories",
+    "add_agent_memory",
+    "learning_rag_search",
+    "get_knowledge_graph",
+    "evaluate_math_expression",
+];
+
+lazy_static::lazy_static! {
+    static ref TOOL_REGISTRY: RwLock<HashMap<String, ToolDefinition>> = RwLock::new(load_registry());
+}
+
+fn get_tools_file() -> PathBuf {
+    let mut path = dirs::data_local_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("claude-cli");
+    let _ = fs::create_dir_all(&path);
+    path.push("tools.json");
+    path
+}
+
+fn tool(name: &str, description: &str, input_schema: Value) -> ToolDefinition {
+    ToolDefinition {
+        name: name.to_string(),
+        description: description.to_string(),
+        input_schema,
+        handler: name.to_string(),
+    }
+}
+
+fn builtin_tools() -> Vec<ToolDefinition> {
+    vec![
+        tool(
+            "execute_command",
+            "Run a read-only system command (always in safe mode)",
+            json!({
+                "type": "object",
+                "properties": {
+                    "command": { "type": "string" },
+                    "cwd": { "type": "string" }
+                },
+                "required": ["command"]
+            }),
+        ),
+        tool(
+            "get_agent_memories",
+            "Fetch an agent's most recent memories",
+            json!({
+                "type": "object",
+                "properties": {
+                    "agent": { "type": "string" },
+                    "limit": { "type": "integer" }
+                },
+                "required": ["agent"]
+            }),
+        ),
+        tool(
+            "add_agent_memory",
+            "Store a memory for an agent",
+            json!({
+                "type": "object",
+                "properties": {
+                    "agent": { "type": "string" },
+                    "entry_type": { "type": "string" },
+                    "content": { "type": "string" },
+                    "tags": { "type": "string" },
+                    "importance": { "type": "number" }
+                },
+                "required": ["agent", "content"]
+            }),
+        ),
+        tool(
+            "learning_rag_search",
+            "Semantic search over the RAG document store",
+            json!({
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string" },
+                    "top_k": { "type": "integer" }
+                },
+                "required": ["query"]
+            }),
+        ),
+        tool(
+            "get_knowledge_graph",
+            "Return the shared knowledge graph",
+            json!({ "type": "object", "properties": {} }),
+        ),
+        tool(
+            "evaluate_math_expression",
+            "Evaluate an arithmetic expression: numbers, + - * / ^, parentheses, sqrt(), log() (natural), abs()",
+            json!({
+                "type": "object",
+                "properties": {
+                    "expression": { "type": "string" },
+                    "precision": { "type": "integer" }
+                },
+                "required": ["expression"]
+            }),
+        ),
+    ]
+}
+
+fn builtin_registry() -> HashMap<String, ToolDefinition> {
+    builtin_tools()
+        .into_iter()
+        .map(|t| (t.name.clone(), t))
+        .collect()
+}
+
+/// Built-in tools overlaid with the user tools saved to `tools.json`
+fn load_registry() -> HashMap<String, ToolDefinition> {
+    let mut registry = builtin_registry();
+
+    if let Ok(content) = fs::read_to_string(get_tools_file()) {
+        match serde_json::from_str::<Vec<ToolDefinition>>(&content) {
+            Ok(saved) => overlay_saved_tools(&mut registry, saved),
+            Err(e) => tracing::warn!("Ignoring invalid tools.json: {}", e),
+        }
+    }
+
+    registry
+}
+
+/// Built-in tools are named after their handler and always come from code
+fn is_builtin(name: &str) -> bool {
+    HANDLERS.contains(&name)
+}
+
+fn validate_tool(tool: &ToolDefinition) -> Result<(), String> {
+    if tool.name.trim().is_empty() {
+        return Err("Tool name cannot be empty".to_string());
+    }
+    if is_builtin(&tool.name) {
+        return Err(format!("Cannot replace built-in tool '{}'", tool.name));
+    }
+    if !HANDLERS.contains(&tool.handler.as_str()) {
+        return Err(format!(
+            "Unknown tool handler '{}'. Available: {}",
+            tool.handler,
+            HANDLERS.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// Add saved tools to the registry, skipping any that would shadow a built-in
+/// (a stale copy would hide later changes to its definition)
+fn overlay_saved_tools(registry: &mut HashMap<String, ToolDefinition>, saved: Vec<ToolDefinition>) {
+    for tool in saved {
+        match validate_tool(&tool) {
+            Ok(()) => {
+                registry.insert(tool.name.clone(), tool);
+            }
+            Err(e) => tracing::warn!("Ignoring saved tool: {}", e),
+        }
+    }
+}
+
+fn user_tools(registry: &HashMap<String, ToolDefinition>) -> Vec<&ToolDefinition> {
+    let mut tools: Vec<&ToolDefinition> =
+        registry.values().filter(|t| !is_builtin(&t.name)).collect();
+    tools.sort_by(|a, b| a.name.cmp(&b.name));
+    tools
+}
+
+/// Only user-registered tools are persisted; built-ins are rebuilt from code
+fn save_registry(registry: &HashMap<String, ToolDefinition>) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(&user_tools(registry)).map_err(|e| e.to_string())?;
+    fs::write(get_tools_file(), content).map_err(|e| e.to_string())
+}
+
+fn parse_input<T: DeserializeOwned>(tool: &str, input: Value) -> Result<T, String> {
+    serde_json::from_value(input).map_err(|e| format!("Invalid input for tool '{}': {}", tool, e))
+}
+
+fn to_value<T: Serialize>(value: T) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|e| e.to_string())
+}
+
+#[derive(Deserialize)]
+struct CommandInput {
+    command: String,
+    cwd: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct MemoriesInput {
+    agent: String,
+    limit: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct AddMemoryInput {
+    agent: String,
+    entry_type: Option<String>,
+    content: String,
+    tags: Option<String>,
+    importance: Option<f32>,
+}
+
+#[derive(Deserialize)]
+struct RagSearchInput {
+    query: String,
+    top_k: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct MathInput {
+    expression: String,
+    precision: Option<u32>,
+}
+
+async fn dispatch(tool: &ToolDefinition, input: Value) -> Result<Value, String> {
+    let name = tool.name.as_str();
+
+    match tool.handler.as_str() {
+        "execute_command" => {
+            let args: CommandInput = parse_input(name, input)?;
+            to_value(agentic::execute_command(args.command, true, args.cwd).await?)
+        }
+        "get_agent_memories" => {
+            let args: MemoriesInput = parse_input(name, input)?;
+            to_value(memory::get_agent_memories(args.agent, args.limit)?)
+        }
+        "add_agent_memory" => {
+            let args: AddMemoryInput = parse_input(name, input)?;
+            to_value(memory::add_agent_memory(
+                args.agent,
+                args.entry_type.unwrap_or_else(|| "fact".to_string()),
+                args.content,
+                args.tags.unwrap_or_default(),
+                args.importance,
+            )?)
+        }
+        "learning_rag_search" => {
+            let args: RagSearchInput = parse_input(name, input)?;
+            to_value(learning::learning_rag_search(args.query, args.top_k).await?)
+        }
+        "get_knowledge_graph" => to_value(memory::get_knowledge_graph()?),
+        "evaluate_math_expression" => {
+            let args: MathInput = parse_input(name, input)?;
+            let value = math::evaluate_math_expression(args.expression).await?;
+            Ok(json!({
+                "value": value,
+                "formatted": math::format_math_result(value, args.precision),
+            }))
+        }
+        other => Err(format!("Unknown tool handler: {}", other)),
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Add or replace a user tool. Its handler must be one of the built-in
+/// handlers, and built-in tools themselves cannot be replaced.
+#[tauri::command]
+pub fn register_tool(tool: ToolDefinition) -> Result<(), String> {
+    validate_tool(&tool)?;
+
+    let mut registry = TOOL_REGISTRY.write();
+    registry.insert(tool.name.clone(), tool);
+    save_registry(&registry)
+}
+
+#[tauri::command]
+pub fn list_tools() -> Result<Vec<ToolDefinition>, String> {
+    let mut tools: Vec<ToolDefinition> = TOOL_REGISTRY.read().values().cloned().collect();
+    tools.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(tools)
+}
+
+#[tauri::command]
+pub async fn invoke_tool(name: String, input: Value) -> Result<Value, String> {
+    let tool = TOOL_REGISTRY
+        .read()
+        .get(&name)
+        .cloned()
+        .ok_or_else(|| format!("Tool not found: {}", name))?;
+
+    tracing::info!("Invoking tool {} ({})", tool.name, tool.handler);
+    dispatch(&tool, input).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom_tool(name: &str, handler: &str) -> ToolDefinition {
+        ToolDefinition {
+            name: name.to_string(),
+            description: "Custom persona".to_string(),
+            input_schema: json!({ "type": "object", "properties": {} }),
+            handler: handler.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_saved_tools_overlay_builtins() {
+        let calculator = custom_tool("calculator", "evaluate_math_expression");
+        assert!(validate_tool(&calculator).is_ok());
+        assert!(validate_tool(&custom_tool("shell", "rm")).is_err());
+        assert!(validate_tool(&custom_tool(" ", "execute_command")).is_err());
+
+        let mut registry = builtin_registry();
+        overlay_saved_tools(&mut registry, vec![calculator, custom_tool("shell", "rm")]);
+
+        assert_eq!(registry.len(), HANDLERS.len() + 1);
+        assert_eq!(registry["calculator"].handler, "evaluate_math_expression");
+        assert!(!registry.contains_key("shell"));
+
+        let saved: Vec<&str> = user_tools(&registry)
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect();
+        assert_eq!(saved, vec!["calculator"]);
+    }
+
+    #[test]
+    fn test_saved_tools_cannot_shadow_builtins() {
+        let stale = custom_tool("execute_command", "execute_command");
+        assert!(validate_tool(&stale).is_err());
+
+        let mut registry = builtin_registry();
+        overlay_saved_tools(&mut registry, vec![stale]);
+
+        let execute = &registry["execute_command"];
+        assert_ne!(execute.description, "Custom persona");
+        assert!(execute.input_schema["properties"].get("cwd").is_some());
+        assert!(user_tools(&registry).is_empty());
+    }
+}
@@ -0,0 +1,123 @@
+//! Update check against the latest GitHub release
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Window};
+
+const RELEASES_URL: &str = "https://api.github.com/repos/EPS-AI-SOLUTIONS/GeminiCLI/releases/latest";
+const CACHE_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateInfo {
+    pub current_version: String,
+    pub latest_version: String,
+    pub update_available: bool,
+    pub release_url: String,
+    pub changelog: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    html_url: String,
+    #[serde(default)]
+    body: Option<String>,
+}
+
+lazy_static::lazy_static! {
+    static ref UPDATE_CACHE: RwLock<Option<(Instant, UpdateInfo)>> = RwLock::new(None);
+}
+
+/// Numeric components of a version tag like `v1.2.3` or `1.2.3-beta`
+fn version_parts(version: &str) -> Vec<u64> {
+    version
+        .trim()
+        .trim_start_matches(['v', 'V'])
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+/// True when `latest` is a strictly higher version than `current`
+fn is_newer(latest: &str, current: &str) -> bool {
+    let (mut latest, mut current) = (version_parts(latest), version_parts(current));
+    let len = latest.len().max(current.len());
+    latest.resize(len, 0);
+    current.resize(len, 0);
+    latest > current
+}
+
+async fn fetch_update_info() -> Result<UpdateInfo, String> {
+    let current_version = env!("CARGO_PKG_VERSION").to_string();
+
+    let release: GithubRelease = reqwest::Client::new()
+        .get(RELEASES_URL)
+        // GitHub rejects API requests without a User-Agent
+        .header("User-Agent", format!("claude-gui/{}", current_version))
+        .header("Accept", "application/vnd.github+json")
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await
+        .map_err(|e| format!("Update check failed: {}", e))?
+        .error_for_status()
+        .map_err(|e| format!("Update check failed: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse release: {}", e))?;
+
+    let latest_version = release.tag_name.trim_start_matches(['v', 'V']).to_string();
+
+    Ok(UpdateInfo {
+        update_available: is_newer(&latest_version, &current_version),
+        current_version,
+        latest_version,
+        release_url: release.html_url,
+        changelog: release.body.unwrap_or_default(),
+    })
+}
+
+/// Check GitHub for a newer release. Results are cached for six hours.
+/// Emits `update-available` when a newer version exists.
+#[tauri::command]
+pub async fn check_for_updates(window: Window) -> Result<UpdateInfo, String> {
+    let cached = UPDATE_CACHE
+        .read()
+        .as_ref()
+        .filter(|(checked_at, _)| checked_at.elapsed() < CACHE_TTL)
+        .map(|(_, info)| info.clone());
+
+    let info = match cached {
+        Some(info) => info,
+        None => {
+            let info = fetch_update_info().await?;
+            *UPDATE_CACHE.write() = Some((Instant::now(), info.clone()));
+            info
+        }
+    };
+
+    if info.update_available {
+        let _ = window.emit("update-available", &info);
+    }
+
+    Ok(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_comparison() {
+        assert!(is_newer("v0.2.0", "0.1.0"));
+        assert!(is_newer("0.1.10", "0.1.9"));
+        assert!(is_newer("1.0", "0.9.9"));
+        assert!(!is_newer("0.1.0", "0.1.0"));
+        assert!(!is_newer("v0.1", "0.1.0"));
+        assert!(!is_newer("0.1.0-beta", "0.1.0"));
+        assert!(!is_newer("0.0.9", "0.1.0"));
+    }
+}
@@ -0,0 +1,35 @@
+//! Small shared helpers
+
+/// Return at most `max_chars` characters of `s`.
+///
+/// Slices on a char boundary, so unlike `&s[..n]` it never panics on
+/// multi-byte (non-Latin) text.
+pub fn truncate_chars(s: &str, max_chars: usize) -> &str {
+    match s.char_indices().nth(max_chars) {
+        Some((byte_idx, _)) => &s[..byte_idx],
+        None => s,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_ascii() {
+        assert_eq!(truncate_chars("hello world", 5), "hello");
+        assert_eq!(truncate_chars("hello", 5), "hello");
+        assert_eq!(truncate_chars("hi", 5), "hi");
+        assert_eq!(truncate_chars("", 5), "");
+        assert_eq!(truncate_chars("abc", 0), "");
+    }
+
+    #[test]
+    fn test_truncate_multibyte_at_boundary() {
+        // "ż" and "ó" are two bytes each; byte index 3 would split "ó"
+        assert_eq!(truncate_chars("żółw", 2), "żó");
+        assert_eq!(truncate_chars("żółw", 4), "żółw");
+        assert_eq!(truncate_chars("日本語テキスト", 3), "日本語");
+        assert_eq!(truncate_chars("a🦀b", 2), "a🦀");
+    }
+}
@@ -46,6 +46,8 @@ interface RagDocument {
   content: string;
   score?: number;
   metadata?: Record<string, unknown>;
+  source: string;
+  source_type?: string;
 }
 
 interface TrainingExample {
@@ -216,6 +218,8 @@ export function LearningPanel() {
       await invoke('learning_rag_add', {
         id,
         content: addDocContent,
+        source: 'gui',
+        sourceType: 'manual',
         metadata: { source: 'manual', added_via: 'gui' },
       });
       setAddDocContent('');
@@ -1,6 +1,7 @@
 import { useState, useRef, useEffect, useCallback, DragEvent } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
+import { formatError } from '../utils/format';
 
 // Check if running in Tauri (v2 uses __TAURI_INTERNALS__)
 const isTauri = () => typeof window !== 'undefined' && ('__TAURI__' in window || '__TAURI_INTERNALS__' in window);
@@ -35,7 +36,7 @@ const browserOllamaApi = {
       });
     } catch (e) {
       console.error('[OllamaChat] Fetch error:', e);
-      throw new Error(`Connection failed: ${e}`);
+      throw new Error(`Connection failed: ${formatError(e)}`);
     }
 
     if (!res.ok) {
@@ -107,6 +108,10 @@ interface StreamChunk {
   done: boolean;
   model?: string;
   total_tokens?: number;
+  elapsed_ms?: number;
+  tokens_per_sec?: number;
+  /** Set on the final chunk; `unknown` means Ollama gave no reason we recognise */
+  finish_reason?: 'length' | 'cancelled' | 'end_of_generation' | 'unknown';
 }
 
 interface Attachment {
@@ -125,6 +130,8 @@ interface Message {
   timestamp: Date;
   model?: string;
   streaming?: boolean;
+  /** Generation hit max tokens; shown as a note, never sent back to the model */
+  truncated?: boolean;
 }
 
 export function OllamaChatView() {
@@ -208,6 +215,8 @@ export function OllamaChatView() {
   // Reference to capture final response for Vilgefortz
   const responseBufferRef = useRef<string>('');
   const lastUserPromptRef = useRef<string>('');
+  // Chunks are broadcast to every window; only follow our own stream
+  const streamIdRef = useRef<string | null>(null);
 
   // Listen for streaming chunks with Vilgefortz post-processing
   useEffect(() => {
@@ -215,6 +224,7 @@ export function OllamaChatView() {
 
     const unlisten = listen<StreamChunk>('ollama-stream-chunk', async (event) => {
       const chunk = event.payload;
+      if (chunk.id !== streamIdRef.current) return;
 
       // Accumulate response
       responseBufferRef.current += chunk.token;
@@ -228,6 +238,7 @@ export function OllamaChatView() {
               ...lastMsg,
               content: lastMsg.content + chunk.token,
               streaming: !chunk.done,
+              truncated: chunk.finish_reason === 'length',
             },
           ];
         }
@@ -429,9 +440,12 @@ export function OllamaChatView() {
         // Tauri mode: use IPC with full pipeline
         await processPrompt(finalContent, async (enrichedPrompt) => {
           chatMessages.push({ role: 'user', content: enrichedPrompt });
+          const streamId = crypto.randomUUID();
+          streamIdRef.current = streamId;
           await invoke('ollama_chat', {
             model: selectedModel,
             messages: chatMessages,
+            stream: { stream_id: streamId },
           });
           return '';
         });
@@ -473,7 +487,8 @@ export function OllamaChatView() {
             ...prev.slice(0, -1),
             {
               ...last,
-              content: `Error: ${e}`,
+              // Tauri commands reject with { code, message }
+              content: `Error: ${formatError(e)}`,
               streaming: false,
             },
           ];
@@ -684,6 +699,9 @@ export function OllamaChatView() {
                     >
                       {msg.content || (msg.streaming ? '▌' : '')}
                     </ReactMarkdown>
+                    {msg.truncated && (
+                      <p className="mt-2 text-xs italic text-matrix-text-dim">(truncated at max tokens)</p>
+                    )}
                   </div>
                 </div>
               </div>
@@ -18,6 +18,7 @@ export interface ChatSession {
   message_count: number;
   model?: string;
   messages: ChatMessage[];
+  forked_from?: string;
 }
 
 export interface ChatSessionSummary {
@@ -420,6 +420,8 @@ async function alzurTrainingCheck(ctx: PipelineContext): Promise<void> {
         await invoke('learning_rag_add', {
           id: sampleId,
           content: sampleContent,
+          source: 'alzur-pipeline',
+          sourceType: 'generated',
           metadata: {
             type: 'training_sample',
             prompt: ctx.originalPrompt.slice(0, 200),
@@ -1,6 +1,7 @@
 import { useState, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import type { ChatSession, ChatSessionSummary } from './useChatHistory';
+import { formatError } from '../utils/format';
 
 // AI-generated metadata for sessions
 export interface SessionAIMetadata {
@@ -158,7 +159,7 @@ export function useSessionAI() {
           .replace(/^Title:\s*/i, '')
           .slice(0, 60);
       } catch (e) {
-        setError(`Failed to generate title: ${e}`);
+        setError(`Failed to generate title: ${formatError(e)}`);
         throw e;
       } finally {
         setIsProcessing(false);
@@ -180,7 +181,7 @@ export function useSessionAI() {
         const prompt = PROMPTS.summary.replace('{content}', content);
         return await generateText(prompt);
       } catch (e) {
-        setError(`Failed to generate summary: ${e}`);
+        setError(`Failed to generate summary: ${formatError(e)}`);
         throw e;
       } finally {
         setIsProcessing(false);
@@ -211,7 +212,7 @@ export function useSessionAI() {
 
         return [...new Set(tags)].slice(0, 4);
       } catch (e) {
-        setError(`Failed to generate tags: ${e}`);
+        setError(`Failed to generate tags: ${formatError(e)}`);
         throw e;
       } finally {
         setIsProcessing(false);
@@ -240,7 +241,7 @@ export function useSessionAI() {
           .filter((k) => k.length > 2 && k.length < 30)
           .slice(0, 20);
       } catch (e) {
-        setError(`Failed to extract keywords: ${e}`);
+        setError(`Failed to extract keywords: ${formatError(e)}`);
         throw e;
       } finally {
         setIsProcessing(false);
@@ -278,7 +279,7 @@ export function useSessionAI() {
 
         return metadata;
       } catch (e) {
-        setError(`Failed to process session: ${e}`);
+        setError(`Failed to process session: ${formatError(e)}`);
         throw e;
       } finally {
         setIsProcessing(false);
@@ -350,7 +351,7 @@ export function useSessionAI() {
           .filter((s) => s.similarity && s.similarity > 0)
           .sort((a, b) => (b.similarity || 0) - (a.similarity || 0));
       } catch (e) {
-        setError(`Search failed: ${e}`);
+        setError(`Search failed: ${formatError(e)}`);
         return [];
       } finally {
         setIsProcessing(false);
@@ -407,7 +408,7 @@ export function useSessionAI() {
           .sort((a, b) => (b.similarity || 0) - (a.similarity || 0))
           .slice(0, 5);
       } catch (e) {
-        setError(`Failed to find related sessions: ${e}`);
+        setError(`Failed to find related sessions: ${formatError(e)}`);
         return [];
       } finally {
         setIsProcessing(false);
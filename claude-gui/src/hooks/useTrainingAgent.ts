@@ -1,5 +1,6 @@
 import { useState, useCallback, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 
 /**
  * Alzur - AI Trainer Agent
@@ -12,6 +13,17 @@ import { invoke } from '@tauri-apps/api/core';
  * 5. Managing model checkpoints
  */
 
+// Payload of the backend `training-progress` event; only the final event
+// has a state other than 'running'
+interface TrainingProgressEvent {
+  job_id: string;
+  state: 'running' | 'completed' | 'failed' | 'cancelled';
+  status?: string;
+  error?: string;
+  percentage?: number;
+  layer?: number;
+}
+
 // Training data entry format
 export interface TrainingEntry {
   id: string;
@@ -289,18 +301,48 @@ export function useTrainingAgent() {
       job.status = 'training';
       setCurrentJob({ ...job });
 
-      // Call backend training command
-      const result = await invoke<{ success: boolean; modelPath?: string; error?: string }>('start_model_training', {
-        config: {
-          base_model: fullConfig.baseModel,
-          output_model: fullConfig.outputModel,
-          dataset_path: datasetPath,
-          epochs: fullConfig.epochs,
-          learning_rate: fullConfig.learningRate,
-          batch_size: fullConfig.batchSize,
-        },
+      // Training runs in the background; listen before starting so the
+      // final event cannot be missed
+      const finalEvents = new Map<string, TrainingProgressEvent>();
+      let onFinal: (() => void) | null = null;
+      const unlisten = await listen<TrainingProgressEvent>('training-progress', (event) => {
+        if (event.payload.state !== 'running') {
+          finalEvents.set(event.payload.job_id, event.payload);
+          onFinal?.();
+        }
       });
 
+      let finalEvent: TrainingProgressEvent;
+      let result: { success: boolean; job_id: string; modelPath?: string; error?: string };
+      try {
+        result = await invoke('start_model_training', {
+          config: {
+            base_model: fullConfig.baseModel,
+            output_model: fullConfig.outputModel,
+            dataset_path: datasetPath,
+            epochs: fullConfig.epochs,
+            learning_rate: fullConfig.learningRate,
+            batch_size: fullConfig.batchSize,
+          },
+        });
+
+        finalEvent = await new Promise<TrainingProgressEvent>((resolve) => {
+          const check = () => {
+            const event = finalEvents.get(result.job_id);
+            if (event) resolve(event);
+          };
+          onFinal = check;
+          check();
+        });
+      } finally {
+        unlisten();
+      }
+
+      if (finalEvent.state !== 'completed') {
+        const error = finalEvent.state === 'cancelled' ? 'Training cancelled' : finalEvent.error;
+        result = { ...result, success: false, error };
+      }
+
       if (result.success) {
         job.status = 'completed';
         job.completedAt = Date.now();
@@ -95,6 +95,7 @@ export interface BatchResult {
   response: string | null;
   error: string | null;
   duration_ms: number;
+  queued_ms: number;
 }
 
 export const parallelIpc = {
@@ -112,9 +113,10 @@ export const parallelIpc = {
   batchGenerate: (
     model: string,
     prompts: string[],
-    options?: Record<string, unknown>
+    options?: Record<string, unknown>,
+    maxConcurrent?: number
   ): Promise<BatchResult[]> =>
-    safeInvoke('ollama_batch_generate', { model, prompts, options }),
+    safeInvoke('ollama_batch_generate', { model, prompts, options, maxConcurrent }),
 };
 
 // ============================================================================
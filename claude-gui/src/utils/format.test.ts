@@ -1,5 +1,5 @@
 import { describe, it, expect } from 'vitest';
-import { formatBytes, formatSizeGB, formatNumber, formatSimilarity } from './format';
+import { formatBytes, formatSizeGB, formatNumber, formatSimilarity, formatError } from './format';
 
 describe('format utilities', () => {
   describe('formatBytes', () => {
@@ -150,4 +150,24 @@ describe('format utilities', () => {
       expect(formatSimilarity(99.9)).toBe('100');
     });
   });
+
+  describe('formatError', () => {
+    it('should return string errors unchanged', () => {
+      expect(formatError('Ollama is not running')).toBe('Ollama is not running');
+    });
+
+    it('should use the message of AppError objects', () => {
+      expect(formatError({ code: 'model_not_found', message: 'Model not found: llama3' })).toBe('Model not found: llama3');
+    });
+
+    it('should use the message of Error instances', () => {
+      expect(formatError(new Error('boom'))).toBe('boom');
+    });
+
+    it('should stringify other values', () => {
+      expect(formatError(42)).toBe('42');
+      expect(formatError(null)).toBe('null');
+      expect(formatError({ message: 1 })).toBe('[object Object]');
+    });
+  });
 });
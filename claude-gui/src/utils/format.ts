@@ -57,3 +57,21 @@ export function formatNumber(
 export function formatSimilarity(similarity?: number | null): string {
   return formatNumber(similarity, 0, '0');
 }
+
+/**
+ * Format a caught error for display
+ * Tauri commands reject with either a string or an AppError object
+ * ({ code, message }), which would otherwise render as "[object Object]"
+ */
+export function formatError(error: unknown): string {
+  if (typeof error === 'string') {
+    return error;
+  }
+  if (typeof error === 'object' && error !== null && 'message' in error) {
+    const { message } = error as { message: unknown };
+    if (typeof message === 'string') {
+      return message;
+    }
+  }
+  return String(error);
+}
@@ -308,6 +308,14 @@ async fn execute_gemini_stream(prompt: &str, window: &tauri::Window) -> Result<S
     }
 }
 
+/// Return at most `max_chars` characters, slicing on a char boundary
+fn truncate_chars(s: &str, max_chars: usize) -> &str {
+    match s.char_indices().nth(max_chars) {
+        Some((byte_idx, _)) => &s[..byte_idx],
+        None => s,
+    }
+}
+
 /// Analyze prompt complexity (1-5)
 fn analyze_complexity(prompt: &str) -> u8 {
     let lower = prompt.to_lowercase();
@@ -376,7 +384,7 @@ async fn hydra_query(prompt: String) -> Result<AiResponse, String> {
     let start = std::time::Instant::now();
     let complexity = analyze_complexity(&prompt);
 
-    info!("HYDRA query [complexity={}]: {}", complexity, &prompt[..prompt.len().min(50)]);
+    info!("HYDRA query [complexity={}]: {}", complexity, truncate_chars(&prompt, 50));
 
     // Check Ollama availability
     let (ollama_available, _models) = check_ollama().await;
@@ -455,7 +463,7 @@ async fn hydra_query_stream(prompt: String, window: tauri::Window) -> Result<AiR
     let start = std::time::Instant::now();
     let complexity = analyze_complexity(&prompt);
 
-    info!("HYDRA stream query [complexity={}]: {}", complexity, &prompt[..prompt.len().min(50)]);
+    info!("HYDRA stream query [complexity={}]: {}", complexity, truncate_chars(&prompt, 50));
 
     // Emit start event
     let _ = window.emit("stream", StreamEvent {